@@ -0,0 +1,205 @@
+// 批量导入模块 - 扫描文件夹并将支持的文件提交到后台处理队列
+// 支持递归扫描和包含/排除通配符，返回每个文件的提交结果
+
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use crate::commands::job::JobQueue;
+use crate::commands::process::ProcessOptions;
+use crate::commands::DbState;
+use crate::ocr;
+
+/// 单次导入最多扫描的文件数
+const MAX_SCAN_FILES: usize = 20_000;
+
+/// 文件夹导入选项
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FolderImportOptions {
+    pub recursive: bool,          // 是否扫描子文件夹
+    pub include: Vec<String>,     // 包含的通配符（如 `*.pdf`），为空时包含全部
+    pub exclude: Vec<String>,     // 排除的通配符（如 `**/备份/**`）
+    pub person_id: Option<i32>,   // 关联的人员
+    pub process: ProcessOptions,  // 提取选项
+}
+
+/// 单个文件的导入结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderImportItem {
+    pub path: String,
+    pub file_type: Option<String>,  // 识别出的文件类型
+    pub job_id: Option<i32>,        // 已提交时的任务 ID
+    pub error: Option<String>,      // 未提交的原因（不支持的格式、读取失败等）
+}
+
+/// 文件夹导入汇总
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderImportSummary {
+    pub scanned: usize,               // 扫描到的文件数
+    pub excluded: usize,              // 被通配符、隐藏文件规则排除的文件数
+    pub queued: usize,                // 已提交处理的文件数
+    pub skipped: usize,               // 不支持或无法读取的文件数
+    pub items: Vec<FolderImportItem>, // 每个候选文件的结果
+}
+
+/// 扫描文件夹并提交其中支持的文件
+///
+/// # 参数
+/// * `dir` - 文件夹路径
+/// * `options` - 扫描和处理选项
+///
+/// # 返回
+/// 导入汇总，处理进度通过 `process-job` 事件推送
+#[tauri::command]
+pub async fn import_folder(
+    dir: String,
+    options: Option<FolderImportOptions>,
+    state: State<'_, DbState>,
+    queue: State<'_, JobQueue>,
+) -> Result<FolderImportSummary, String> {
+    let options = options.unwrap_or_default();
+    let root = Path::new(&dir);
+    if !root.is_dir() {
+        return Err(format!("文件夹不存在: {}", dir));
+    }
+
+    let mut files = Vec::new();
+    scan_dir(root, options.recursive, &mut files)?;
+
+    let mut summary = FolderImportSummary {
+        scanned: files.len(),
+        ..Default::default()
+    };
+
+    for path in files {
+        let relative = relative_path(root, &path);
+        if !is_selected(&relative, &options.include, &options.exclude) {
+            summary.excluded += 1;
+            continue;
+        }
+
+        let path_str = path.to_string_lossy().into_owned();
+        let mut item = FolderImportItem {
+            path: path_str.clone(),
+            file_type: None,
+            job_id: None,
+            error: None,
+        };
+
+        // 先识别类型，不支持的文件不进入队列
+        let submitted = ocr::detect::detect_file_type(&path_str).and_then(|file_type| {
+            item.file_type = Some(file_type.to_string());
+            queue.submit(&state, &path_str, Some(file_type), options.person_id, &options.process)
+        });
+
+        match submitted {
+            Ok(job_id) => {
+                item.job_id = Some(job_id);
+                summary.queued += 1;
+            }
+            Err(e) => {
+                item.error = Some(e);
+                summary.skipped += 1;
+            }
+        }
+        summary.items.push(item);
+    }
+
+    Ok(summary)
+}
+
+/// 列出文件夹中的文件（按路径排序，不跟随符号链接）
+fn scan_dir(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|e| format!("读取文件夹 {} 失败: {}", dir.display(), e))?
+        .flatten()
+        .collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let Ok(file_type) = entry.file_type() else { continue };
+        let path = entry.path();
+
+        if file_type.is_dir() {
+            if recursive {
+                scan_dir(&path, recursive, files)?;
+            }
+        } else if file_type.is_file() {
+            if files.len() >= MAX_SCAN_FILES {
+                return Err(format!("文件夹中的文件超过 {} 个，请分批导入", MAX_SCAN_FILES));
+            }
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// 相对于导入文件夹的路径，统一以 / 分隔
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 文件是否满足包含/排除条件
+///
+/// 隐藏文件和 Office 临时文件（`~$` 开头）始终排除；
+/// 不含 `/` 的通配符只匹配文件名，含 `/` 的匹配相对路径
+pub fn is_selected(relative: &str, include: &[String], exclude: &[String]) -> bool {
+    let name = relative.rsplit('/').next().unwrap_or(relative);
+    if relative.split('/').any(|part| part.starts_with('.')) || name.starts_with("~$") {
+        return false;
+    }
+
+    let matches = |pattern: &String| {
+        let target = if pattern.contains('/') { relative } else { name };
+        glob_match(pattern.trim_start_matches("./"), target)
+    };
+
+    (include.is_empty() || include.iter().any(matches)) && !exclude.iter().any(matches)
+}
+
+/// 通配符匹配（不区分大小写）
+///
+/// `*` 匹配不含 `/` 的任意字符，`**` 匹配任意层级目录，`?` 匹配单个字符
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    glob_match_from(&pattern, &text)
+}
+
+fn glob_match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            // `**/` 可以匹配零层目录
+            if let Some(after) = rest.strip_prefix(&['/']) {
+                if glob_match_from(after, text) {
+                    return true;
+                }
+            }
+            (0..=text.len()).any(|i| glob_match_from(rest, &text[i..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if glob_match_from(rest, &text[i..]) {
+                    return true;
+                }
+                if i < text.len() && text[i] == '/' {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => !text.is_empty() && text[0] != '/' && glob_match_from(&pattern[1..], &text[1..]),
+        Some(c) => !text.is_empty() && text[0] == *c && glob_match_from(&pattern[1..], &text[1..]),
+    }
+}
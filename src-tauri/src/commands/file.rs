@@ -3,8 +3,17 @@
 
 use crate::db::{self, FileRecord};
 use crate::commands::person::DbState;
+use serde::Serialize;
 use tauri::State;
 
+/// 内容相同的一组文件
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub content_hash: String,
+    pub files: Vec<FileRecord>,  // 按添加顺序排列，第一个为最早导入的文件
+}
+
 /// 添加文件记录
 #[tauri::command]
 pub fn add_file(
@@ -27,10 +36,30 @@ pub fn get_files(state: State<DbState>) -> Result<Vec<FileRecord>, String> {
     db::get_files(&conn).map_err(|e| e.to_string())
 }
 
-/// 删除文件记录
+/// 删除文件记录（含子文件、表格记录和处理日志）
 #[tauri::command]
 pub fn delete_file(id: i32, state: State<DbState>) -> Result<(), String> {
     let conn = state.get_conn()?;
-    db::delete_file(&conn, id).map_err(|e| e.to_string())?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    db::delete_file_tree(&tx, id).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
     Ok(())
 }
+
+/// 获取内容重复的文件分组
+#[tauri::command]
+pub fn get_duplicate_groups(state: State<DbState>) -> Result<Vec<DuplicateGroup>, String> {
+    let conn = state.get_conn()?;
+    let files = db::get_duplicate_files(&conn).map_err(|e| e.to_string())?;
+
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    for file in files {
+        let Some(hash) = file.content_hash.clone() else { continue };
+        match groups.last_mut() {
+            Some(group) if group.content_hash == hash => group.files.push(file),
+            _ => groups.push(DuplicateGroup { content_hash: hash, files: vec![file] }),
+        }
+    }
+
+    Ok(groups)
+}
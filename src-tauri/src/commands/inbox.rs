@@ -0,0 +1,326 @@
+// 收件箱模块 - 监视指定文件夹并自动处理新文件
+// 轮询收件箱，文件大小和修改时间稳定后移入 processing 子文件夹并提交处理任务，
+// 任务结束后按结果移入 processed 或 failed 子文件夹；设置和已提交文件的记录保存在数据库中，
+// 出错时推送 inbox-error 事件
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::commands::batch;
+use crate::commands::job::{JobQueue, JobStatus};
+use crate::commands::process::{self, ProcessOptions};
+use crate::commands::DbState;
+use crate::db;
+
+/// 设置表中的键
+const SETTINGS_KEY: &str = "inbox";
+
+/// 设置表中记录已提交文件的键：processing 中的文件路径 → 任务 ID
+/// 按该记录判断文件是否已提交，任务记录被清除后也不会重复提交
+const PENDING_KEY: &str = "inbox_pending";
+
+/// 收件箱错误事件名
+pub const INBOX_EVENT: &str = "inbox-error";
+
+/// 轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 处理中、处理成功、处理失败的子文件夹名
+const PROCESSING_DIR: &str = "processing";
+const PROCESSED_DIR: &str = "processed";
+const FAILED_DIR: &str = "failed";
+
+/// 收件箱设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InboxSettings {
+    pub enabled: bool,             // 是否启用自动处理
+    pub path: Option<String>,      // 收件箱文件夹
+    pub person_id: Option<i32>,    // 自动关联的人员
+    pub process: ProcessOptions,   // 提取选项
+}
+
+/// 收件箱错误（随事件推送）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxError {
+    pub file_path: Option<String>,  // 出错的文件，轮询整体失败时为空
+    pub message: String,
+}
+
+impl InboxError {
+    fn new(path: Option<&Path>, message: String) -> Self {
+        Self {
+            file_path: path.map(|p| p.to_string_lossy().into_owned()),
+            message,
+        }
+    }
+}
+
+/// 收件箱监视器
+pub struct InboxWatcher {
+    settings: Arc<Mutex<InboxSettings>>,
+}
+
+/// 上一次轮询时看到的文件大小和修改时间
+type Snapshot = HashMap<PathBuf, (u64, Option<SystemTime>)>;
+
+/// 已提交的文件：processing 中的文件路径 → 任务 ID
+type Pending = HashMap<String, i32>;
+
+impl InboxWatcher {
+    /// 读取保存的设置并启动监视线程
+    pub fn start(app: AppHandle) -> Result<Self, String> {
+        let settings = {
+            let state = app.state::<DbState>();
+            let conn = state.get_conn()?;
+            db::get_setting(&conn, SETTINGS_KEY)
+                .map_err(|e| e.to_string())?
+                .and_then(|value| serde_json::from_str(&value).ok())
+                .unwrap_or_default()
+        };
+
+        let settings = Arc::new(Mutex::new(settings));
+        let shared = Arc::clone(&settings);
+
+        std::thread::spawn(move || {
+            let mut snapshot = Snapshot::new();
+            let mut reported = Vec::new();
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+
+                let current = match shared.lock() {
+                    Ok(settings) => settings.clone(),
+                    Err(_) => return,
+                };
+
+                match current.path.as_deref() {
+                    Some(path) if current.enabled => {
+                        let mut errors = Vec::new();
+                        if let Err(e) = poll(&app, Path::new(path), &current, &mut snapshot, &mut errors) {
+                            errors.push(InboxError::new(None, e));
+                        }
+                        // 每次轮询都会重试出错的文件，同一错误只报告一次
+                        for error in errors.iter().filter(|e| !reported.contains(*e)) {
+                            report(&app, error);
+                        }
+                        reported = errors;
+                    }
+                    _ => {
+                        snapshot.clear();
+                        reported.clear();
+                    }
+                }
+            }
+        });
+
+        Ok(Self { settings })
+    }
+}
+
+/// 报告收件箱错误：推送事件，涉及具体文件时同时写入处理日志
+fn report(app: &AppHandle, error: &InboxError) {
+    if let Some(file_path) = &error.file_path {
+        let state = app.state::<DbState>();
+        if let Ok(conn) = state.get_conn() {
+            let _ = db::add_processing_log(&conn, None, file_path, "failed", "[]", "[]", Some(&error.message), 0);
+        }
+    }
+    let _ = app.emit(INBOX_EVENT, error.clone());
+}
+
+/// 轮询一次：先归档已结束的任务，再提交新出现且已写入完成的文件
+/// 单个文件出错时记入 `errors` 并继续处理其余文件
+fn poll(
+    app: &AppHandle,
+    inbox: &Path,
+    settings: &InboxSettings,
+    snapshot: &mut Snapshot,
+    errors: &mut Vec<InboxError>,
+) -> Result<(), String> {
+    let processing = inbox.join(PROCESSING_DIR);
+    let state = app.state::<DbState>();
+    let queue = app.state::<JobQueue>();
+    let mut pending = load_pending(&state)?;
+
+    // 已提交的文件：任务结束后归档，文件已不存在时移除记录
+    let mut finished = Vec::new();
+    for (file_path, &job_id) in &pending {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            finished.push(file_path.clone());
+            continue;
+        }
+        match archive(&state, inbox, path, job_id) {
+            Ok(true) => finished.push(file_path.clone()),
+            Ok(false) => {}
+            Err(e) => errors.push(InboxError::new(Some(path), e)),
+        }
+    }
+    if !finished.is_empty() {
+        for file_path in &finished {
+            pending.remove(file_path);
+        }
+        save_pending(&state, &pending)?;
+    }
+
+    // 提交文件并立即记录，应用在两步之间退出时下次轮询会重新提交
+    let submit = |path: &Path, pending: &mut Pending| -> Result<(), String> {
+        let file_path = path.to_string_lossy().into_owned();
+        let job_id = queue.submit(&state, &file_path, None, settings.person_id, &settings.process)?;
+        pending.insert(file_path, job_id);
+        save_pending(&state, pending)
+    };
+
+    // processing 中没有提交记录的文件（移入后应用退出或提交失败）重新提交
+    for path in list_files(&processing) {
+        if !pending.contains_key(&*path.to_string_lossy()) {
+            if let Err(e) = submit(&path, &mut pending) {
+                errors.push(InboxError::new(Some(&path), e));
+            }
+        }
+    }
+
+    // 收件箱中的新文件：与上次轮询相比大小和修改时间都未变化时才认为写入完成
+    let mut next = Snapshot::new();
+    for path in list_files(inbox) {
+        let relative = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        if !batch::is_selected(&relative, &[], &[]) {
+            continue;
+        }
+
+        let Ok(metadata) = std::fs::metadata(&path) else { continue };
+        let current = (metadata.len(), metadata.modified().ok());
+
+        if snapshot.get(&path) == Some(&current) {
+            let submitted = move_into(&path, &processing).and_then(|moved| submit(&moved, &mut pending));
+            if let Err(e) = submitted {
+                errors.push(InboxError::new(Some(&path), e));
+            }
+        } else {
+            next.insert(path, current);
+        }
+    }
+    *snapshot = next;
+
+    Ok(())
+}
+
+/// 任务结束后将文件移入 processed 或 failed 子文件夹
+///
+/// 任务记录已被清除时按该路径最近一次的处理日志判断结果
+///
+/// # 返回
+/// 任务仍在排队或处理中时为 `false`
+fn archive(state: &DbState, inbox: &Path, path: &Path, job_id: i32) -> Result<bool, String> {
+    let file_path = path.to_string_lossy();
+
+    let (completed, job_id, file_id) = {
+        let conn = state.get_conn()?;
+        let job = match db::get_job(&conn, job_id) {
+            Ok(job) => Some(job).filter(|job| job.file_path == file_path),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        match job {
+            Some(job) => match JobStatus::parse(&job.status) {
+                JobStatus::Completed => (true, Some(job.id), job.file_id),
+                JobStatus::Failed | JobStatus::Cancelled => (false, Some(job.id), job.file_id),
+                JobStatus::Queued | JobStatus::Running => return Ok(false),
+            },
+            None => {
+                let log = db::get_latest_processing_log_by_path(&conn, &file_path).map_err(|e| e.to_string())?;
+                let completed = log.as_ref().is_some_and(|log| log.status == "completed");
+                (completed, None, log.and_then(|log| log.file_id))
+            }
+        }
+    };
+
+    let target_dir = inbox.join(if completed { PROCESSED_DIR } else { FAILED_DIR });
+    let target = move_into(path, &target_dir)?;
+    let target = target.to_string_lossy();
+
+    // 重复文件跳过处理时 file_id 指向已有记录，其原始路径不受影响
+    let conn = state.get_conn()?;
+    if let Some(job_id) = job_id {
+        db::update_job_file_path(&conn, job_id, &target).map_err(|e| e.to_string())?;
+    }
+    if let Some(file_id) = file_id {
+        db::move_original_path(&conn, file_id, &file_path, &target).map_err(|e| e.to_string())?;
+    }
+    Ok(true)
+}
+
+/// 读取已提交文件的记录
+fn load_pending(state: &DbState) -> Result<Pending, String> {
+    let conn = state.get_conn()?;
+    Ok(db::get_setting(&conn, PENDING_KEY)
+        .map_err(|e| e.to_string())?
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default())
+}
+
+/// 保存已提交文件的记录
+fn save_pending(state: &DbState, pending: &Pending) -> Result<(), String> {
+    let value = serde_json::to_string(pending).map_err(|e| e.to_string())?;
+    let conn = state.get_conn()?;
+    db::set_setting(&conn, PENDING_KEY, &value).map_err(|e| e.to_string())
+}
+
+/// 列出文件夹中的文件（不含子文件夹）
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .map(|e| e.path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// 将文件移入目标文件夹，重名时追加序号
+fn move_into(path: &Path, dir: &Path) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+    let target = process::unique_path(dir, name);
+    std::fs::rename(path, &target).map_err(|e| format!("移动文件 {} 失败: {}", path.display(), e))?;
+    Ok(target)
+}
+
+/// 获取收件箱设置
+#[tauri::command]
+pub async fn get_inbox_settings(watcher: State<'_, InboxWatcher>) -> Result<InboxSettings, String> {
+    let settings = watcher.settings.lock().map_err(|e| e.to_string())?;
+    Ok(settings.clone())
+}
+
+/// 保存收件箱设置，立即生效
+#[tauri::command]
+pub async fn set_inbox_settings(
+    settings: InboxSettings,
+    state: State<'_, DbState>,
+    watcher: State<'_, InboxWatcher>,
+) -> Result<(), String> {
+    if settings.enabled {
+        let Some(path) = settings.path.as_deref().filter(|p| !p.trim().is_empty()) else {
+            return Err("请先设置收件箱文件夹".to_string());
+        };
+        if !Path::new(path).is_dir() {
+            return Err(format!("文件夹不存在: {}", path));
+        }
+    }
+
+    let value = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    {
+        let conn = state.get_conn()?;
+        db::set_setting(&conn, SETTINGS_KEY, &value).map_err(|e| e.to_string())?;
+    }
+
+    *watcher.settings.lock().map_err(|e| e.to_string())? = settings;
+    Ok(())
+}
@@ -0,0 +1,433 @@
+// 后台任务模块 - 文件处理任务队列
+// 提交文件后立即返回任务 ID，由后台工作线程执行提取，并通过 Tauri 事件推送阶段和进度；
+// 任务状态保存在数据库中，支持取消、重试，应用重启后恢复排队中的任务
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::commands::process::{self, ProcessContext, ProcessOptions, ProcessProgress, ProcessStage};
+use crate::commands::DbState;
+use crate::db::{self, JobRecord};
+
+/// 任务状态变化和进度事件名
+pub const JOB_EVENT: &str = "process-job";
+
+/// 后台工作线程数（OCR 占用 CPU 较多，不宜过多）
+const WORKER_COUNT: usize = 2;
+
+/// 应用退出时仍在处理的任务在下次启动时标记的失败原因
+const INTERRUPTED: &str = "应用退出时任务未完成，请重试";
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,     // 排队中
+    Running,    // 处理中
+    Completed,  // 已完成
+    Failed,     // 失败
+    Cancelled,  // 已取消
+}
+
+impl JobStatus {
+    /// 数据库中保存的状态名
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// 解析数据库中保存的状态名
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "queued" => JobStatus::Queued,
+            "running" => JobStatus::Running,
+            "completed" => JobStatus::Completed,
+            "cancelled" => JobStatus::Cancelled,
+            _ => JobStatus::Failed,
+        }
+    }
+
+    /// 任务是否已结束
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+/// 任务信息（同时作为事件内容推送给前端）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub id: i32,
+    pub file_path: String,
+    pub status: JobStatus,
+    pub progress: Option<ProcessProgress>,  // 最近一次进度
+    pub percent: u8,                        // 当前文件的完成百分比
+    pub file_id: Option<i32>,               // 完成后保存的文件记录 ID
+    pub table_count: usize,                 // 完成后保存的表格数（含子文件）
+    pub errors: Vec<String>,                // 子文件处理失败信息
+    pub error: Option<String>,              // 任务失败原因
+}
+
+impl JobInfo {
+    /// 从数据库记录恢复任务信息（进度不持久化）
+    fn from_record(record: &JobRecord) -> Self {
+        let status = JobStatus::parse(&record.status);
+        Self {
+            id: record.id,
+            file_path: record.file_path.clone(),
+            status,
+            progress: None,
+            percent: if status == JobStatus::Completed { 100 } else { 0 },
+            file_id: record.file_id,
+            table_count: 0,
+            errors: Vec::new(),
+            error: record.error.clone(),
+        }
+    }
+}
+
+/// 排队中的任务
+struct Job {
+    id: i32,
+    file_path: String,
+    file_type: Option<String>,
+    person_id: Option<i32>,
+    options: ProcessOptions,
+    cancel: Arc<AtomicBool>,
+}
+
+/// 任务队列
+pub struct JobQueue {
+    app: AppHandle,
+    sender: Mutex<Sender<Job>>,
+    jobs: Arc<Mutex<HashMap<i32, JobInfo>>>,
+    cancels: Arc<Mutex<HashMap<i32, Arc<AtomicBool>>>>,  // 未结束任务的取消标记
+}
+
+impl JobQueue {
+    /// 创建任务队列并启动后台工作线程
+    ///
+    /// 上次退出时仍在处理的任务标记为失败（可重试），排队中的任务重新入队，
+    /// 中断的重新处理遗留的临时表格记录和中断的流式导入遗留的未完成文件记录被删除
+    pub fn start(app: AppHandle) -> Result<Self, String> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let queue = Self {
+            app: app.clone(),
+            sender: Mutex::new(sender),
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            cancels: Arc::new(Mutex::new(HashMap::new())),
+        };
+
+        for _ in 0..WORKER_COUNT {
+            let app = app.clone();
+            let receiver = Arc::clone(&receiver);
+            let jobs = Arc::clone(&queue.jobs);
+            let cancels = Arc::clone(&queue.cancels);
+            std::thread::spawn(move || worker_loop(app, receiver, jobs, cancels));
+        }
+
+        let records = {
+            let state = app.state::<DbState>();
+            let conn = state.get_conn()?;
+            db::delete_staging_table_records(&conn).map_err(|e| e.to_string())?;
+            db::delete_incomplete_files(&conn).map_err(|e| e.to_string())?;
+            let mut records = db::get_jobs(&conn).map_err(|e| e.to_string())?;
+            for record in records.iter_mut().filter(|r| r.status == JobStatus::Running.as_str()) {
+                db::update_job_status(&conn, record.id, JobStatus::Failed.as_str(), None, Some(INTERRUPTED))
+                    .map_err(|e| e.to_string())?;
+                record.status = JobStatus::Failed.as_str().to_string();
+                record.error = Some(INTERRUPTED.to_string());
+            }
+            records
+        };
+
+        for record in &records {
+            queue.jobs.lock().map_err(|e| e.to_string())?.insert(record.id, JobInfo::from_record(record));
+            if record.status == JobStatus::Queued.as_str() {
+                queue.enqueue(record)?;
+            }
+        }
+
+        Ok(queue)
+    }
+
+    /// 将任务记录放入队列
+    fn enqueue(&self, record: &JobRecord) -> Result<(), String> {
+        let options: ProcessOptions = serde_json::from_str(&record.options).unwrap_or_default();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.cancels
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(record.id, Arc::clone(&cancel));
+
+        let job = Job {
+            id: record.id,
+            file_path: record.file_path.clone(),
+            file_type: record.file_type.clone(),
+            person_id: record.person_id,
+            options,
+            cancel,
+        };
+
+        self.sender
+            .lock()
+            .map_err(|e| e.to_string())?
+            .send(job)
+            .map_err(|e| e.to_string())
+    }
+
+    /// 保存并提交任务，返回任务 ID
+    pub fn submit(
+        &self,
+        state: &DbState,
+        file_path: &str,
+        file_type: Option<&str>,
+        person_id: Option<i32>,
+        options: &ProcessOptions,
+    ) -> Result<i32, String> {
+        let options = serde_json::to_string(options).map_err(|e| e.to_string())?;
+
+        let record = {
+            let conn = state.get_conn()?;
+            let id = db::add_job(&conn, file_path, file_type, person_id, &options).map_err(|e| e.to_string())?;
+            db::get_job(&conn, id).map_err(|e| e.to_string())?
+        };
+
+        let info = JobInfo::from_record(&record);
+        self.jobs.lock().map_err(|e| e.to_string())?.insert(record.id, info.clone());
+        let _ = self.app.emit(JOB_EVENT, info);
+
+        self.enqueue(&record)?;
+        Ok(record.id)
+    }
+
+    /// 更新任务信息并推送事件
+    fn update(&self, id: i32, f: &dyn Fn(&mut JobInfo)) {
+        update_job(&self.app, &self.jobs, id, f);
+    }
+}
+
+/// 工作线程：依次取出任务并执行
+fn worker_loop(
+    app: AppHandle,
+    receiver: Arc<Mutex<Receiver<Job>>>,
+    jobs: Arc<Mutex<HashMap<i32, JobInfo>>>,
+    cancels: Arc<Mutex<HashMap<i32, Arc<AtomicBool>>>>,
+) {
+    loop {
+        // 只在取任务时持有接收端的锁
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+        let Ok(job) = job else { return };
+
+        // 排队期间已取消的任务直接跳过
+        if !job.cancel.load(Ordering::Relaxed) {
+            run_job(&app, &jobs, &job);
+        }
+
+        if let Ok(mut cancels) = cancels.lock() {
+            // 重试后同一任务可能已有新的取消标记
+            if cancels.get(&job.id).is_some_and(|c| Arc::ptr_eq(c, &job.cancel)) {
+                cancels.remove(&job.id);
+            }
+        }
+    }
+}
+
+/// 执行单个任务，更新任务状态并推送事件
+fn run_job(app: &AppHandle, jobs: &Mutex<HashMap<i32, JobInfo>>, job: &Job) {
+    let state = app.state::<DbState>();
+    let update = |f: &dyn Fn(&mut JobInfo)| update_job(app, jobs, job.id, f);
+
+    save_status(&state, job.id, JobStatus::Running, None, None);
+    update(&|info| info.status = JobStatus::Running);
+
+    let on_progress = |progress: ProcessProgress| {
+        update(&|info| {
+            info.percent = percent(&progress);
+            info.progress = Some(progress.clone());
+        });
+    };
+    let ctx = ProcessContext {
+        options: &job.options,
+        state: state.inner(),
+        on_progress: &on_progress,
+        cancel: &job.cancel,
+    };
+
+    // 解析器内部的 panic 只让当前任务失败，不影响工作线程
+    // 注意：release 配置为 panic = "abort"，只有开发构建能在这里捕获，发布版中提取器 panic 仍会终止应用
+    let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        process::run_process(&job.file_path, job.file_type.clone(), job.person_id, &ctx)
+    }))
+    .unwrap_or_else(|_| Err("处理过程中发生内部错误".to_string()));
+
+    match outcome {
+        Ok(result) => {
+            save_status(&state, job.id, JobStatus::Completed, Some(result.file_id), None);
+            update(&|info| {
+                info.status = JobStatus::Completed;
+                info.percent = 100;
+                info.file_id = Some(result.file_id);
+                info.table_count = result.table_count();
+                info.errors = result.all_errors();
+            });
+        }
+        Err(_) if job.cancel.load(Ordering::Relaxed) => {
+            save_status(&state, job.id, JobStatus::Cancelled, None, None);
+            update(&|info| info.status = JobStatus::Cancelled);
+        }
+        Err(e) => {
+            save_status(&state, job.id, JobStatus::Failed, None, Some(&e));
+            update(&|info| {
+                info.status = JobStatus::Failed;
+                info.error = Some(e.clone());
+            });
+        }
+    }
+}
+
+/// 更新内存中的任务信息并推送事件
+fn update_job(app: &AppHandle, jobs: &Mutex<HashMap<i32, JobInfo>>, id: i32, f: &dyn Fn(&mut JobInfo)) {
+    let Ok(mut jobs) = jobs.lock() else { return };
+    if let Some(info) = jobs.get_mut(&id) {
+        f(info);
+        let _ = app.emit(JOB_EVENT, info.clone());
+    }
+}
+
+/// 保存任务状态到数据库（失败时只影响重启后的恢复，不中断处理）
+fn save_status(state: &DbState, id: i32, status: JobStatus, file_id: Option<i32>, error: Option<&str>) {
+    if let Ok(conn) = state.get_conn() {
+        let _ = db::update_job_status(&conn, id, status.as_str(), file_id, error);
+    }
+}
+
+/// 根据处理阶段估算当前文件的完成百分比（提取占 90%，保存占 10%）
+fn percent(progress: &ProcessProgress) -> u8 {
+    let ratio = if progress.total == 0 {
+        0
+    } else {
+        progress.current.min(progress.total) * 100 / progress.total
+    };
+
+    let percent = match progress.stage {
+        ProcessStage::Extracting | ProcessStage::Ocr => ratio * 9 / 10,
+        ProcessStage::Saving => 90 + ratio / 10,
+    };
+    percent as u8
+}
+
+/// 提交文件处理任务
+///
+/// # 参数
+/// 与 `process_file` 相同
+///
+/// # 返回
+/// 任务 ID，处理进度通过 `process-job` 事件推送
+#[tauri::command]
+pub async fn submit_process_job(
+    file_path: String,
+    file_type: Option<String>,
+    person_id: Option<i32>,
+    options: Option<ProcessOptions>,
+    state: State<'_, DbState>,
+    queue: State<'_, JobQueue>,
+) -> Result<i32, String> {
+    let options = options.unwrap_or_default();
+    queue.submit(&state, &file_path, file_type.as_deref(), person_id, &options)
+}
+
+/// 取消任务
+///
+/// 排队中的任务立即取消；处理中的任务在下一页、下一批数据或下一个子文件之前停止
+#[tauri::command]
+pub async fn cancel_process_job(job_id: i32, state: State<'_, DbState>, queue: State<'_, JobQueue>) -> Result<(), String> {
+    let status = queue
+        .jobs
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&job_id)
+        .map(|info| info.status)
+        .ok_or_else(|| format!("任务不存在: {}", job_id))?;
+
+    if status.is_finished() {
+        return Err("任务已结束，无法取消".to_string());
+    }
+
+    if let Some(cancel) = queue.cancels.lock().map_err(|e| e.to_string())?.get(&job_id) {
+        cancel.store(true, Ordering::Relaxed);
+    }
+
+    // 处理中的任务由工作线程在停止后更新状态
+    if status == JobStatus::Queued {
+        save_status(&state, job_id, JobStatus::Cancelled, None, None);
+        queue.update(job_id, &|info| info.status = JobStatus::Cancelled);
+    }
+
+    Ok(())
+}
+
+/// 重试失败或已取消的任务（使用原有的文件路径和选项）
+#[tauri::command]
+pub async fn retry_process_job(job_id: i32, state: State<'_, DbState>, queue: State<'_, JobQueue>) -> Result<(), String> {
+    let record = {
+        let conn = state.get_conn()?;
+        let record = db::get_job(&conn, job_id).map_err(|_| format!("任务不存在: {}", job_id))?;
+
+        match JobStatus::parse(&record.status) {
+            JobStatus::Failed | JobStatus::Cancelled => {}
+            _ => return Err("只能重试失败或已取消的任务".to_string()),
+        }
+
+        db::update_job_status(&conn, job_id, JobStatus::Queued.as_str(), None, None)
+            .map_err(|e| e.to_string())?;
+        db::get_job(&conn, job_id).map_err(|e| e.to_string())?
+    };
+
+    let info = JobInfo::from_record(&record);
+    queue.update(job_id, &|current| *current = info.clone());
+
+    queue.enqueue(&record)
+}
+
+/// 获取所有任务（按提交顺序）
+#[tauri::command]
+pub async fn get_process_jobs(queue: State<'_, JobQueue>) -> Result<Vec<JobInfo>, String> {
+    let jobs = queue.jobs.lock().map_err(|e| e.to_string())?;
+    let mut list: Vec<JobInfo> = jobs.values().cloned().collect();
+    list.sort_by_key(|job| job.id);
+    Ok(list)
+}
+
+/// 获取单个任务
+#[tauri::command]
+pub async fn get_process_job(job_id: i32, queue: State<'_, JobQueue>) -> Result<JobInfo, String> {
+    let jobs = queue.jobs.lock().map_err(|e| e.to_string())?;
+    jobs.get(&job_id).cloned().ok_or_else(|| format!("任务不存在: {}", job_id))
+}
+
+/// 清除已结束的任务
+#[tauri::command]
+pub async fn clear_finished_jobs(state: State<'_, DbState>, queue: State<'_, JobQueue>) -> Result<(), String> {
+    {
+        let conn = state.get_conn()?;
+        db::delete_finished_jobs(&conn).map_err(|e| e.to_string())?;
+    }
+
+    let mut jobs = queue.jobs.lock().map_err(|e| e.to_string())?;
+    jobs.retain(|_, job| !job.status.is_finished());
+    Ok(())
+}
@@ -6,6 +6,10 @@ pub mod record;
 pub mod export;
 pub mod file;
 pub mod process;
+pub mod job;
+pub mod batch;
+pub mod inbox;
+pub mod store;
 
 // 导出 person 模块中的公共内容
 pub use person::DbState;
@@ -24,10 +28,26 @@ pub use export::{export_to_excel, export_to_csv};
 pub use file::get_files;
 pub use file::add_file;
 pub use file::delete_file;
+pub use file::get_duplicate_groups;
 
 // 导出 process 模块中的公共内容
 pub use process::process_file;
 pub use process::get_file_table_records;
+pub use process::reprocess_file;
+pub use process::get_processing_logs;
+
+// 导出 job 模块中的公共内容
+pub use job::{submit_process_job, get_process_jobs, get_process_job, clear_finished_jobs};
+pub use job::{cancel_process_job, retry_process_job};
+
+// 导出 batch 模块中的公共内容
+pub use batch::import_folder;
+
+// 导出 inbox 模块中的公共内容
+pub use inbox::{get_inbox_settings, set_inbox_settings};
+
+// 导出 store 模块中的公共内容
+pub use store::{open_stored_file, export_stored_file, cleanup_store};
 
 // 数据库状态共享结构体
 use rusqlite::Connection;
@@ -1,74 +1,916 @@
 // 文件处理命令模块 - 处理各种文件类型的统一接口
-// 支持 PDF、图片（OCR）、Word、Excel 文件的表格提取
+// 支持 PDF、图片（OCR）、Word、Excel、CSV/TSV、HTML/MHTML、ODT、RTF、PPTX 文件的表格提取，
+// 以及邮件（.eml/.msg）附件、压缩包（.zip/.7z）成员的批量处理
 
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
 use tauri::State;
+use serde::{Deserialize, Serialize};
 use serde_json::to_string;
-use crate::ocr::{self, ExtractedTable};
+use crate::ocr::{self, ExtractedTable, TableSource};
+use crate::ocr::excel::ExcelOptions;
 use crate::db::{self, TableRecord};
-use crate::commands::DbState;
+use crate::commands::{store, DbState};
+
+/// 流式导入时每个表格记录的最大行数
+const STREAM_BATCH_ROWS: usize = 5000;
+
+/// 超过该大小的 Excel 文件自动使用流式导入（20 MB）
+const STREAM_THRESHOLD_BYTES: u64 = 20 * 1024 * 1024;
+
+/// 邮件附件的保存目录（与数据库文件位于同一工作目录）
+const ATTACHMENT_DIR: &str = "attachments";
+
+/// 压缩包的解压目录（与数据库文件位于同一工作目录）
+const ARCHIVE_DIR: &str = "archives";
+
+/// 邮件、压缩包相互嵌套时的最大处理层数
+const MAX_NESTING_DEPTH: usize = 3;
+
+/// 任务被取消时返回的错误信息
+pub const CANCELLED: &str = "任务已取消";
+
+/// OCR 置信度低于该值时记录警告
+const LOW_CONFIDENCE: f32 = 60.0;
+
+/// 处理结果中每个表格预览的行数
+const PREVIEW_ROWS: usize = 10;
+
+/// 查询处理日志时最多返回的条数
+const MAX_LOGS: u32 = 200;
 
 /// 处理文件并提取表格
 ///
 /// # 参数
 /// * `file_path` - 文件路径
-/// * `file_type` - 文件类型（pdf、image、word、excel）
+/// * `file_type` - 可选的文件类型（pdf、image、word、excel、csv、html、odt、rtf、pptx、email、archive），
+///   未提供时根据文件内容自动识别
 /// * `person_id` - 可选的人员 ID，用于关联记录
+/// * `options` - 可选的提取选项，未提供时使用默认值
 /// * `state` - 数据库状态
 ///
 /// # 返回
-/// 处理结果，包含文件 ID 和表格信息
+/// 处理结果，包含文件 ID 和表格信息；邮件附件和压缩包成员的处理结果位于 `children`
 #[tauri::command]
 pub async fn process_file(
     file_path: String,
-    file_type: String,
+    file_type: Option<String>,
     person_id: Option<i32>,
+    options: Option<ProcessOptions>,
     state: State<DbState>,
 ) -> Result<ProcessResult, String> {
-    // 根据文件类型处理
-    let tables = match file_type.to_lowercase().as_str() {
-        "pdf" => process_pdf(&file_path)?,
-        "image" => process_image(&file_path)?,
-        "word" => process_word(&file_path)?,
-        "excel" => process_excel(&file_path)?,
-        _ => return Err(format!("不支持的文件类型: {}", file_type)),
+    let options = options.unwrap_or_default();
+    let cancel = AtomicBool::new(false);
+    let ctx = ProcessContext {
+        options: &options,
+        state: state.inner(),
+        on_progress: &|_| {},
+        cancel: &cancel,
+    };
+
+    run_process(&file_path, file_type, person_id, &ctx)
+}
+
+/// 使用新的提取选项重新处理已导入的文件
+///
+/// # 参数
+/// * `file_id` - 文件记录 ID
+/// * `options` - 新的提取选项，未提供时使用默认值
+/// * `reprocess` - 保存方式，未提供时替换当前版本并保留手动修改过的表格
+///
+/// # 返回
+/// 保存的版本号和表格数；邮件只重新提取正文中的表格，附件需单独重新处理
+#[tauri::command]
+pub async fn reprocess_file(
+    file_id: i32,
+    options: Option<ProcessOptions>,
+    reprocess: Option<ReprocessOptions>,
+    state: State<'_, DbState>,
+) -> Result<ReprocessResult, String> {
+    let options = options.unwrap_or_default();
+    let reprocess = reprocess.unwrap_or_default();
+    let cancel = AtomicBool::new(false);
+    let ctx = ProcessContext {
+        options: &options,
+        state: state.inner(),
+        on_progress: &|_| {},
+        cancel: &cancel,
     };
 
+    let file = {
+        let conn = state.get_conn()?;
+        db::get_file(&conn, file_id).map_err(|e| e.to_string())?
+    };
+
+    let mut log = ProcessLog::new();
+    let result = run_reprocess(&file, &reprocess, &ctx, &mut log);
+
+    if let Ok(conn) = state.get_conn() {
+        let _ = log.save(&conn, Some(file.id), &file.file_path, result.as_ref().err());
+    }
+    result
+}
+
+/// 重新提取文件的表格并保存
+fn run_reprocess(
+    file: &db::FileRecord,
+    reprocess: &ReprocessOptions,
+    ctx: &ProcessContext,
+    log: &mut ProcessLog,
+) -> Result<ReprocessResult, String> {
+    if !std::path::Path::new(&file.file_path).is_file() {
+        return Err(format!("文件不存在: {}", file.file_path));
+    }
+
+    let path = file.file_path.as_str();
+    let file_type = file.file_type.to_lowercase();
+    if matches!(file_type.as_str(), "archive" | "zip" | "7z" | "other") {
+        return Err("该文件没有可提取的表格".to_string());
+    }
+
+    let mut writer = ReprocessWriter::new(ctx, file, reprocess)?;
+    let result = extract_reprocessed(path, &file_type, ctx, log, &mut writer).and_then(|_| writer.finish(log));
+    if result.is_err() {
+        writer.abort();
+    }
+    result
+}
+
+/// 重新提取文件的表格，逐个交给写入器保存
+fn extract_reprocessed(
+    path: &str,
+    file_type: &str,
+    ctx: &ProcessContext,
+    log: &mut ProcessLog,
+    writer: &mut ReprocessWriter,
+) -> Result<(), String> {
+    let tables = match file_type {
+        "email" | "eml" | "msg" => {
+            log.stage(ProcessStage::Extracting);
+            let message = ocr::email::EmailProcessor::parse_file(path)?;
+            ocr::email::EmailProcessor::extract_tables(&message)
+        }
+        // 与首次导入保持相同的分批方式，各批次的来源区域才能与原表格对应
+        "excel" if should_stream_excel(path, &ctx.options.excel) => {
+            log.stage(ProcessStage::Saving);
+            warn_auto_streaming(&ctx.options.excel, log);
+            let summary = ocr::excel_stream::ExcelStreamer::stream_tables(path, &ctx.options.excel, STREAM_BATCH_ROWS, |table| {
+                ctx.check_cancelled()?;
+                writer.write(&table)
+            })?;
+            summary.warnings.into_iter().for_each(|warning| log.warn(warning));
+            return Ok(());
+        }
+        _ => extract_tables(path, file_type, ctx, log)?,
+    };
+
+    log.stage(ProcessStage::Saving);
+    for table in &tables {
+        writer.write(table)?;
+    }
+    Ok(())
+}
+
+/// 重新处理时逐个保存表格记录
+///
+/// 新表格先写入读取时不可见的临时版本（负的新版本号），每个表格单独占用数据库连接；
+/// 全部写入后替换当前版本或启用为新版本，中途崩溃遗留的临时记录在下次启动时删除。保留手动修改时按来源位置（工作表、区域、页码等）对应，
+/// 修改过的表格替代来源位置相同的新表格，找不到对应表格的修改不保留并记录警告
+struct ReprocessWriter<'a> {
+    ctx: &'a ProcessContext<'a>,
+    file: &'a db::FileRecord,
+    options: &'a ReprocessOptions,
+    current: i32,
+    staging: i32,
+    edits: Vec<(serde_json::Value, String)>,  // 尚未对应的手动修改：(来源位置, 表格内容)
+    table_count: usize,
+    kept_edits: usize,
+}
+
+impl<'a> ReprocessWriter<'a> {
+    fn new(ctx: &'a ProcessContext<'a>, file: &'a db::FileRecord, options: &'a ReprocessOptions) -> Result<Self, String> {
+        let conn = ctx.state.get_conn()?;
+        let current = db::get_latest_version(&conn, file.id).map_err(|e| e.to_string())?;
+
+        let mut edits = Vec::new();
+        if options.keep_edits {
+            let previous = db::get_table_records_by_version(&conn, file.id, current).map_err(|e| e.to_string())?;
+            for record in previous.into_iter().filter(|r| r.edited) {
+                let source = serde_json::from_str::<serde_json::Value>(&record.content)
+                    .ok()
+                    .and_then(|mut content| content.get_mut("source").map(serde_json::Value::take))
+                    .unwrap_or_default();
+                edits.push((source, record.content));
+            }
+        }
+
+        Ok(Self {
+            ctx,
+            file,
+            options,
+            current,
+            staging: -(current + 1),
+            edits,
+            table_count: 0,
+            kept_edits: 0,
+        })
+    }
+
+    /// 保存一个表格，有来源位置相同的手动修改时保存修改后的内容
+    fn write(&mut self, table: &ExtractedTable) -> Result<(), String> {
+        let edit = if table.source.is_empty() {
+            None
+        } else {
+            let source = serde_json::to_value(&table.source).map_err(|e| e.to_string())?;
+            self.edits.iter().position(|(s, _)| *s == source)
+        };
+
+        let (content, edited) = match edit {
+            Some(index) => {
+                self.kept_edits += 1;
+                (self.edits.remove(index).1, true)
+            }
+            None => (to_string(table).map_err(|e| e.to_string())?, false),
+        };
+
+        let conn = self.ctx.state.get_conn()?;
+        db::add_versioned_table_record(&conn, self.file.id, self.file.person_id, &content, self.staging, edited)
+            .map_err(|e| e.to_string())?;
+        self.table_count += 1;
+        Ok(())
+    }
+
+    /// 启用新写入的记录
+    fn finish(&self, log: &mut ProcessLog) -> Result<ReprocessResult, String> {
+        let version = match self.options.mode {
+            ReprocessMode::Replace => self.current,
+            ReprocessMode::NewVersion => self.current + 1,
+        };
+
+        // 删除旧记录和启用新记录要么都完成，要么都不生效
+        let conn = self.ctx.state.get_conn()?;
+        in_transaction(&conn, |conn| {
+            if self.options.mode == ReprocessMode::Replace {
+                db::delete_table_records_by_version(conn, self.file.id, self.current).map_err(|e| e.to_string())?;
+            }
+            db::set_table_records_version(conn, self.file.id, self.staging, version).map_err(|e| e.to_string())
+        })?;
+
+        if !self.edits.is_empty() {
+            log.warn(format!(
+                "{} 个手动修改过的表格找不到来源位置相同的新表格，未保留",
+                self.edits.len()
+            ));
+        }
+
+        Ok(ReprocessResult {
+            file_id: self.file.id,
+            version,
+            table_count: self.table_count,
+            kept_edits: self.kept_edits,
+            unmatched_edits: self.edits.len(),
+        })
+    }
+
+    /// 出错或取消时删除已写入的临时版本
+    fn abort(&self) {
+        if let Ok(conn) = self.ctx.state.get_conn() {
+            let _ = db::delete_table_records_by_version(&conn, self.file.id, self.staging);
+        }
+    }
+}
+
+/// 识别文件类型并处理文件（供命令和后台任务共用）
+///
+/// # 参数
+/// * `file_type` - 调用方指定的类型优先，为空时按文件内容识别
+pub fn run_process(
+    file_path: &str,
+    file_type: Option<String>,
+    person_id: Option<i32>,
+    ctx: &ProcessContext,
+) -> Result<ProcessResult, String> {
+    let file_type = match file_type.filter(|t| !t.trim().is_empty()) {
+        Some(file_type) => file_type,
+        None => ocr::detect::detect_file_type(file_path)?.to_string(),
+    };
+
+    // 按内容哈希查找已导入的同一文件（只比较顶层文件，不含附件和压缩包成员）
+    let content_hash = file_sha256(file_path)?;
+    let existing: Vec<i32> = {
+        let conn = ctx.state.get_conn()?;
+        db::get_files_by_hash(&conn, &content_hash)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|f| f.parent_id.is_none())
+            .map(|f| f.id)
+            .collect()
+    };
+
+    if let (Some(&first), DuplicatePolicy::Skip) = (existing.first(), ctx.options.duplicate) {
+        return Ok(ProcessResult {
+            file_id: first,
+            file_name: file_name(file_path).to_string(),
+            duplicate_of: Some(first),
+            ..Default::default()
+        });
+    }
+
+    // 替换时已有记录在新文件保存的同一事务中删除，提取失败时保持不变
+    let replaces: &[i32] = match ctx.options.duplicate {
+        DuplicatePolicy::Replace => &existing,
+        DuplicatePolicy::Skip | DuplicatePolicy::KeepBoth => &[],
+    };
+    let origin = Origin {
+        content_hash: Some(&content_hash),
+        replaces,
+        ..Default::default()
+    };
+    let mut result = process_path(file_path, &file_type, person_id, origin, ctx)?;
+    if ctx.options.duplicate == DuplicatePolicy::KeepBoth {
+        result.duplicate_of = existing.first().copied();
+    }
+    Ok(result)
+}
+
+/// 计算文件内容的 SHA-256（十六进制小写）
+pub fn file_sha256(path: &str) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+
+    loop {
+        let n = file.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 处理阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessStage {
+    Extracting,  // 读取和解析文件（PDF 按页）
+    Ocr,         // 图片文字识别
+    Saving,      // 写入数据库
+}
+
+/// 处理进度
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessProgress {
+    pub file_name: String,   // 正在处理的文件（邮件附件、压缩包成员时为子文件）
+    pub stage: ProcessStage,
+    pub current: usize,      // 当前进度，如第 3 页
+    pub total: usize,        // 总量，未知时为 0
+}
+
+/// 处理上下文：提取选项、数据库状态、进度回调和取消标记
+pub struct ProcessContext<'a> {
+    pub options: &'a ProcessOptions,
+    pub state: &'a DbState,
+    pub on_progress: &'a dyn Fn(ProcessProgress),
+    pub cancel: &'a AtomicBool,  // 在页、数据批次和子文件之间检查
+}
+
+impl ProcessContext<'_> {
+    /// 报告处理进度
+    fn report(&self, path: &str, stage: ProcessStage, current: usize, total: usize) {
+        (self.on_progress)(ProcessProgress {
+            file_name: file_name(path).to_string(),
+            stage,
+            current,
+            total,
+        });
+    }
+
+    /// 已取消时返回错误，中止后续处理
+    fn check_cancelled(&self) -> Result<(), String> {
+        if self.cancel.load(Ordering::Relaxed) {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// 阶段耗时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageTiming {
+    pub stage: ProcessStage,
+    pub millis: u64,
+}
+
+/// 单个文件的处理日志：各阶段耗时和警告，处理结束后写入数据库
+struct ProcessLog {
+    started: Instant,
+    current: Option<(ProcessStage, Instant)>,
+    stages: Vec<StageTiming>,
+    warnings: Vec<String>,
+}
+
+impl ProcessLog {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            current: None,
+            stages: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// 进入新阶段并结束上一阶段的计时，重复进入同一阶段时继续计时
+    fn stage(&mut self, stage: ProcessStage) {
+        if matches!(self.current, Some((current, _)) if current == stage) {
+            return;
+        }
+        self.finish_stage();
+        self.current = Some((stage, Instant::now()));
+    }
+
+    /// 结束当前阶段的计时（如开始处理子文件前）
+    fn finish_stage(&mut self) {
+        if let Some((stage, started)) = self.current.take() {
+            self.stages.push(StageTiming {
+                stage,
+                millis: started.elapsed().as_millis() as u64,
+            });
+        }
+    }
+
+    fn warn(&mut self, message: String) {
+        self.warnings.push(message);
+    }
+
+    /// 写入处理日志，根据错误信息区分失败和取消
+    fn save(mut self, conn: &Connection, file_id: Option<i32>, file_path: &str, error: Option<&String>) -> Result<(), String> {
+        self.finish_stage();
+
+        let status = match error {
+            None => "completed",
+            Some(e) if e == CANCELLED => "cancelled",
+            Some(_) => "failed",
+        };
+        let stages = to_string(&self.stages).map_err(|e| e.to_string())?;
+        let warnings = to_string(&self.warnings).map_err(|e| e.to_string())?;
+        let duration = self.started.elapsed().as_millis() as i64;
+
+        db::add_processing_log(conn, file_id, file_path, status, &stages, &warnings, error.map(String::as_str), duration)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// 文件的来源信息（子文件的来源文件，顶层文件的重复检测结果）
+#[derive(Debug, Clone, Copy, Default)]
+struct Origin<'a> {
+    parent_id: Option<i32>,         // 来源文件 ID（邮件或压缩包）
+    member_path: Option<&'a str>,   // 在来源文件中的路径
+    depth: usize,                   // 嵌套层数
+    content_hash: Option<&'a str>,  // 已计算的内容哈希
+    replaces: &'a [i32],            // 保存时一并删除的重复文件记录
+}
+
+/// 处理单个文件并保存记录，结束后写入处理日志
+fn process_path(
+    file_path: &str,
+    file_type: &str,
+    person_id: Option<i32>,
+    origin: Origin,
+    ctx: &ProcessContext,
+) -> Result<ProcessResult, String> {
+    let mut log = ProcessLog::new();
+    let result = process_logged(file_path, file_type, person_id, origin, ctx, &mut log).map(|mut result| {
+        result.file_name = file_name(file_path).to_string();
+        result
+    });
+
+    // 子文件处理失败不影响来源文件，作为来源文件的警告记录
+    if let Ok(result) = &result {
+        log.warnings.extend(result.errors.iter().cloned());
+    }
+
+    // 日志写入失败不影响处理结果
+    if let Ok(conn) = ctx.state.get_conn() {
+        let file_id = result.as_ref().ok().map(|r| r.file_id);
+        let _ = log.save(&conn, file_id, file_path, result.as_ref().err());
+    }
+    result
+}
+
+/// 按文件类型处理单个文件
+fn process_logged(
+    file_path: &str,
+    file_type: &str,
+    person_id: Option<i32>,
+    origin: Origin,
+    ctx: &ProcessContext,
+    log: &mut ProcessLog,
+) -> Result<ProcessResult, String> {
+    let file_type = file_type.to_lowercase();
+
+    match file_type.as_str() {
+        "email" | "eml" | "msg" => return process_email(file_path, person_id, origin, ctx, log),
+        "archive" | "zip" | "7z" => return process_archive(file_path, person_id, origin, ctx, log),
+        _ => {}
+    }
+
+    // 大型 Excel 文件边读边写入数据库，避免整表驻留内存
+    if file_type == "excel" && should_stream_excel(file_path, &ctx.options.excel) {
+        return process_excel_streaming(file_path, person_id, origin, ctx, log);
+    }
+
+    let tables = extract_tables(file_path, &file_type, ctx, log)?;
+
+    ctx.check_cancelled()?;
+    ctx.report(file_path, ProcessStage::Saving, 0, tables.len());
+    log.stage(ProcessStage::Saving);
+
+    // 先复制到应用存储，复制期间不占用数据库连接
+    let stored = store_original(file_path, origin.content_hash)?;
+
     // 获取数据库连接
-    let conn = state.get_conn()?;
+    let conn = ctx.state.get_conn()?;
 
-    // 从路径提取文件名
-    let file_name = std::path::Path::new(&file_path)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown");
+    // 在同一事务中保存文件记录和表格记录
+    let (file_id, results) = in_transaction(&conn, |conn| {
+        let file_id = add_file_record(conn, &stored, &file_type, person_id, origin)?;
+        Ok((file_id, save_tables(conn, file_id, person_id, &tables)?))
+    })?;
+
+    Ok(ProcessResult {
+        file_id,
+        tables: results,
+        ..Default::default()
+    })
+}
+
+/// 根据文件类型提取表格（不含邮件和压缩包）
+fn extract_tables(
+    file_path: &str,
+    file_type: &str,
+    ctx: &ProcessContext,
+    log: &mut ProcessLog,
+) -> Result<Vec<ExtractedTable>, String> {
+    ctx.check_cancelled()?;
+    ctx.report(file_path, ProcessStage::Extracting, 0, 0);
+    log.stage(ProcessStage::Extracting);
+
+    let tables = match file_type {
+        "pdf" => process_pdf(file_path, ctx, log),
+        "image" => process_image(file_path, ctx, log),
+        "word" => process_word(file_path),
+        "excel" => process_excel(file_path, &ctx.options.excel),
+        "csv" | "tsv" => process_csv(file_path),
+        "html" | "mhtml" => process_html(file_path),
+        "odt" => process_odt(file_path),
+        "rtf" => process_rtf(file_path),
+        "pptx" | "powerpoint" => process_pptx(file_path),
+        _ => Err(format!("不支持的文件类型: {}", file_type)),
+    }?;
+
+    if tables.iter().all(|t| t.rows.is_empty()) {
+        log.warn("未提取到表格内容".to_string());
+    }
+    Ok(tables)
+}
+
+/// 在一个事务中执行数据库写入，出错时整体回滚，不会留下只保存了部分表格的文件记录
+fn in_transaction<T>(
+    conn: &Connection,
+    save: impl FnOnce(&Connection) -> Result<T, String>,
+) -> Result<T, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    // 返回错误时事务随 tx 释放而回滚
+    let value = save(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(value)
+}
+
+/// 已复制到应用存储的文件
+struct StoredFile {
+    original: String,  // 处理时的文件路径
+    stored: String,    // 应用存储中的副本路径
+    hash: String,      // 文件内容的 SHA-256
+}
+
+/// 将文件复制到应用存储，在打开事务之前调用
+fn store_original(file_path: &str, content_hash: Option<&str>) -> Result<StoredFile, String> {
+    let hash = match content_hash {
+        Some(hash) => hash.to_string(),
+        None => file_sha256(file_path)?,
+    };
+    let stored = store::store_file(file_path, &hash)?;
+
+    Ok(StoredFile {
+        original: file_path.to_string(),
+        stored: stored.to_string_lossy().into_owned(),
+        hash,
+    })
+}
+
+/// 保存文件记录，子文件关联到来源文件
+///
+/// 记录指向存储副本；顶层文件保留原始路径，子文件的临时解压路径处理后即删除，不保留
+fn add_file_record(
+    conn: &Connection,
+    file: &StoredFile,
+    file_type: &str,
+    person_id: Option<i32>,
+    origin: Origin,
+) -> Result<i32, String> {
+    for id in origin.replaces {
+        db::delete_file_tree(conn, *id).map_err(|e| e.to_string())?;
+    }
 
-    // 保存文件记录
-    let file_id = db::add_file(&conn, person_id, file_name, &file_path, &file_type)?;
+    let file_id = db::add_child_file(
+        conn,
+        person_id,
+        origin.parent_id,
+        origin.member_path,
+        file_name(&file.original),
+        &file.stored,
+        file_type,
+    )
+    .map_err(|e| e.to_string())?;
 
-    // 保存表格记录
+    let original = origin.parent_id.is_none().then_some(file.original.as_str());
+    db::set_file_hash(conn, file_id, &file.hash).map_err(|e| e.to_string())?;
+    db::set_stored_path(conn, file_id, &file.stored, original).map_err(|e| e.to_string())?;
+
+    Ok(file_id)
+}
+
+/// 保存表格记录
+fn save_tables(
+    conn: &Connection,
+    file_id: i32,
+    person_id: Option<i32>,
+    tables: &[ExtractedTable],
+) -> Result<Vec<TableResult>, String> {
     let mut results = Vec::new();
 
     for (index, table) in tables.iter().enumerate() {
         let content = to_string(table).map_err(|e| e.to_string())?;
-        db::add_table_record(&conn, file_id, person_id, &content)?;
+        let record_id = db::add_table_record(conn, file_id, person_id, &content).map_err(|e| e.to_string())?;
 
-        results.push(TableResult {
-            index: index as u32,
-            rows: table.rows.len(),
-            cols: table.rows.first().map(|r| r.len()).unwrap_or(0),
-        });
+        results.push(TableResult::new(index, record_id, table));
     }
 
-    Ok(ProcessResult {
+    Ok(results)
+}
+
+/// 处理邮件文件（.eml / .msg）
+/// 保存邮件记录和 HTML 正文中的表格，再将附件保存到附件目录并按类型逐个处理
+fn process_email(
+    path: &str,
+    person_id: Option<i32>,
+    origin: Origin,
+    ctx: &ProcessContext,
+    log: &mut ProcessLog,
+) -> Result<ProcessResult, String> {
+    ctx.report(path, ProcessStage::Extracting, 0, 0);
+    log.stage(ProcessStage::Extracting);
+    let message = ocr::email::EmailProcessor::parse_file(path)?;
+    let tables = ocr::email::EmailProcessor::extract_tables(&message);
+
+    log.stage(ProcessStage::Saving);
+    let stored = store_original(path, origin.content_hash)?;
+    let (file_id, results) = {
+        let conn = ctx.state.get_conn()?;
+        in_transaction(&conn, |conn| {
+            let file_id = add_file_record(conn, &stored, "email", person_id, origin)?;
+            Ok((file_id, save_tables(conn, file_id, person_id, &tables)?))
+        })?
+    };
+
+    let mut result = ProcessResult {
         file_id,
         tables: results,
+        ..Default::default()
+    };
+
+    let dir = std::path::Path::new(ATTACHMENT_DIR).join(file_id.to_string());
+    if !message.attachments.is_empty() {
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+
+    let mut saved = Vec::new();
+    for attachment in message.attachments {
+        let target = unique_path(&dir, &sanitize_file_name(&attachment.file_name));
+
+        match std::fs::write(&target, &attachment.data) {
+            Ok(()) => saved.push((attachment.file_name, target.to_string_lossy().into_owned())),
+            Err(e) => result.errors.push(format!("附件 {} 保存失败: {}", attachment.file_name, e)),
+        }
+    }
+
+    // 附件单独记录处理日志，不计入邮件本身的耗时
+    log.finish_stage();
+    let processed = process_children(&mut result, &saved, person_id, origin.depth, ctx);
+
+    // 附件已复制到应用存储，删除临时保存的文件
+    let _ = std::fs::remove_dir_all(&dir);
+    processed?;
+    Ok(result)
+}
+
+/// 处理压缩包（.zip / .7z）
+/// 保存压缩包记录，将成员解压到解压目录并按类型逐个处理
+fn process_archive(
+    path: &str,
+    person_id: Option<i32>,
+    origin: Origin,
+    ctx: &ProcessContext,
+    log: &mut ProcessLog,
+) -> Result<ProcessResult, String> {
+    log.stage(ProcessStage::Saving);
+    let stored = store_original(path, origin.content_hash)?;
+
+    // 成员需要关联到压缩包记录，先保存未完成的压缩包记录；被替换的重复文件在成员处理完后才删除
+    let file_id = add_incomplete_record(ctx, &stored, "archive", person_id, origin)?;
+
+    let dir = std::path::Path::new(ARCHIVE_DIR).join(file_id.to_string());
+    let limits = ocr::archive::ArchiveLimits::default();
+    ctx.report(path, ProcessStage::Extracting, 0, 0);
+    log.stage(ProcessStage::Extracting);
+
+    let mut result = ProcessResult {
+        file_id,
+        ..Default::default()
+    };
+
+    let processed = ocr::archive::ArchiveProcessor::extract(path, &dir, &limits).and_then(|members| {
+        let members: Vec<(String, String)> = members
+            .into_iter()
+            .map(|m| (m.member_path, m.target.to_string_lossy().into_owned()))
+            .collect();
+
+        // 成员单独记录处理日志，不计入压缩包本身的耗时
+        log.finish_stage();
+        process_children(&mut result, &members, person_id, origin.depth, ctx)
+    });
+
+    // 成员已复制到应用存储，删除解压出的临时文件
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // 解压失败或取消时删除压缩包记录和已保存的成员，不留下不完整的记录
+    if let Err(e) = processed.and_then(|_| complete_file(ctx, file_id, origin.replaces)) {
+        let _ = delete_files(ctx, &[file_id]);
+        return Err(e);
+    }
+    Ok(result)
+}
+
+/// 保存标记为未完成的文件记录，读取时不可见，进程中途退出时在下次启动时删除
+///
+/// 用于需要分多次写入的文件（压缩包、流式导入），被替换的重复文件在 `complete_file` 中删除
+fn add_incomplete_record(
+    ctx: &ProcessContext,
+    file: &StoredFile,
+    file_type: &str,
+    person_id: Option<i32>,
+    origin: Origin,
+) -> Result<i32, String> {
+    let conn = ctx.state.get_conn()?;
+    in_transaction(&conn, |conn| {
+        let file_id = add_file_record(conn, file, file_type, person_id, Origin { replaces: &[], ..origin })?;
+        db::set_file_incomplete(conn, file_id, true).map_err(|e| e.to_string())?;
+        Ok(file_id)
+    })
+}
+
+/// 在一个事务中删除被替换的重复文件并启用未完成的文件记录
+fn complete_file(ctx: &ProcessContext, file_id: i32, replaces: &[i32]) -> Result<(), String> {
+    let conn = ctx.state.get_conn()?;
+    in_transaction(&conn, |conn| {
+        for id in replaces {
+            db::delete_file_tree(conn, *id).map_err(|e| e.to_string())?;
+        }
+        db::set_file_incomplete(conn, file_id, false).map_err(|e| e.to_string())
+    })
+}
+
+/// 在一个事务中删除文件记录及其子文件
+fn delete_files(ctx: &ProcessContext, ids: &[i32]) -> Result<(), String> {
+    let conn = ctx.state.get_conn()?;
+    in_transaction(&conn, |conn| {
+        for id in ids {
+            db::delete_file_tree(conn, *id).map_err(|e| e.to_string())?;
+        }
+        Ok(())
     })
 }
 
+/// 逐个处理邮件附件或压缩包成员，并关联到来源文件
+///
+/// 单个子文件处理失败不影响其他子文件，失败信息记录在 `errors` 中；
+/// 不支持的类型和超过嵌套层数的邮件、压缩包只记录文件，不提取表格
+///
+/// # 参数
+/// * `members` - (来源文件中的路径, 保存后的文件路径) 列表
+fn process_children(
+    result: &mut ProcessResult,
+    members: &[(String, String)],
+    person_id: Option<i32>,
+    depth: usize,
+    ctx: &ProcessContext,
+) -> Result<(), String> {
+    for (member_path, target) in members {
+        ctx.check_cancelled()?;
+
+        let origin = Origin {
+            parent_id: Some(result.file_id),
+            member_path: Some(member_path),
+            depth: depth + 1,
+            ..Default::default()
+        };
+
+        // 附件和压缩包成员按内容识别类型，无法识别的只记录文件
+        let child_type = match ocr::detect::detect_file_type(target).ok() {
+            Some("email" | "archive") if origin.depth >= MAX_NESTING_DEPTH => None,
+            other => other,
+        };
+
+        let Some(child_type) = child_type else {
+            let stored = store_original(target, None)?;
+            let conn = ctx.state.get_conn()?;
+            let child_id = in_transaction(&conn, |conn| add_file_record(conn, &stored, "other", person_id, origin))?;
+            result.children.push(ProcessResult {
+                file_id: child_id,
+                file_name: file_name(target).to_string(),
+                ..Default::default()
+            });
+            continue;
+        };
+
+        match process_path(target, child_type, person_id, origin, ctx) {
+            Ok(child) => result.children.push(child),
+            Err(e) => result.errors.push(format!("{} 处理失败: {}", member_path, e)),
+        }
+    }
+
+    Ok(())
+}
+
+/// 将附件名转换为安全的文件名（去掉路径分隔符和系统保留字符）
+fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    let cleaned = cleaned.trim().trim_matches('.').to_string();
+    if cleaned.is_empty() {
+        "attachment".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// 目录中已存在同名文件时追加序号，如 `表格 (2).xlsx`
+pub fn unique_path(dir: &std::path::Path, name: &str) -> std::path::PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let path = std::path::Path::new(name);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+    let ext = path.extension().and_then(|e| e.to_str());
+
+    (2..)
+        .map(|n| match ext {
+            Some(ext) => dir.join(format!("{} ({}).{}", stem, n, ext)),
+            None => dir.join(format!("{} ({})", stem, n)),
+        })
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
+}
+
 /// 处理 PDF 文件
-fn process_pdf(path: &str) -> Result<Vec<ExtractedTable>, String> {
-    // 提取 PDF 文本
-    let pages = ocr::pdf::PdfProcessor::extract_text(path)?;
+fn process_pdf(path: &str, ctx: &ProcessContext, log: &mut ProcessLog) -> Result<Vec<ExtractedTable>, String> {
+    // 按页提取 PDF 文本并报告进度
+    let pages = ocr::pdf::PdfProcessor::extract_text_with_progress(path, |page, total| {
+        ctx.report(path, ProcessStage::Extracting, page, total);
+        ctx.check_cancelled()
+    })?;
+
+    for page in pages.iter().filter(|p| p.text.trim().is_empty()) {
+        log.warn(format!("第 {} 页没有文本层，可能是扫描页", page.page));
+    }
 
     // 转换为表格格式
     let table = ocr::pdf::PdfProcessor::text_to_table(pages);
@@ -77,10 +919,16 @@ fn process_pdf(path: &str) -> Result<Vec<ExtractedTable>, String> {
 }
 
 /// 处理图片文件（OCR）
-fn process_image(path: &str) -> Result<Vec<ExtractedTable>, String> {
+fn process_image(path: &str, ctx: &ProcessContext, log: &mut ProcessLog) -> Result<Vec<ExtractedTable>, String> {
     // OCR 识别
+    ctx.report(path, ProcessStage::Ocr, 0, 1);
+    log.stage(ProcessStage::Ocr);
     let ocr_result = ocr::ocr::recognize_image(path, ocr::ocr::OcrConfig::default())?;
 
+    if ocr_result.confidence < LOW_CONFIDENCE {
+        log.warn(format!("识别置信度较低：{:.0}%", ocr_result.confidence));
+    }
+
     // 转换为表格格式
     let table = ocr::ocr::ocr_result_to_table(ocr_result);
 
@@ -93,35 +941,236 @@ fn process_word(path: &str) -> Result<Vec<ExtractedTable>, String> {
 }
 
 /// 处理 Excel 文件
-fn process_excel(path: &str) -> Result<Vec<ExtractedTable>, String> {
+fn process_excel(path: &str, options: &ExcelOptions) -> Result<Vec<ExtractedTable>, String> {
     // 提取所有工作表
-    let excel_data = ocr::excel::ExcelProcessor::extract_all(path)?;
+    let excel_data = ocr::excel::ExcelProcessor::extract_with_options(path, options)?;
 
-    // 转换为表格格式
+    // 转换为表格格式，按需将每个工作表拆分为多个表格
     let mut tables = Vec::new();
 
     for sheet in excel_data.sheets {
-        tables.push(ExtractedTable {
-            rows: sheet.data,
-        });
+        tables.extend(ocr::excel::ExcelProcessor::sheet_to_tables(sheet, options.split_blocks));
     }
 
     Ok(tables)
 }
 
+/// 处理 CSV/TSV 文件
+fn process_csv(path: &str) -> Result<Vec<ExtractedTable>, String> {
+    let table = ocr::csv::CsvProcessor::extract_table(path)?;
+    Ok(vec![table])
+}
+
+/// 处理 HTML/MHTML 文件
+fn process_html(path: &str) -> Result<Vec<ExtractedTable>, String> {
+    ocr::html::HtmlProcessor::extract_tables(path)
+}
+
+/// 处理 ODT 文件
+fn process_odt(path: &str) -> Result<Vec<ExtractedTable>, String> {
+    ocr::odt::OdtProcessor::extract_tables(path)
+}
+
+/// 处理 RTF 文件
+fn process_rtf(path: &str) -> Result<Vec<ExtractedTable>, String> {
+    ocr::rtf::RtfProcessor::extract_tables(path)
+}
+
+/// 处理 PowerPoint 文件
+fn process_pptx(path: &str) -> Result<Vec<ExtractedTable>, String> {
+    ocr::pptx::PptxProcessor::extract_tables(path)
+}
+
+/// 是否对 Excel 文件使用流式导入（显式开启或文件超过大小阈值）
+fn should_stream_excel(path: &str, options: &ExcelOptions) -> bool {
+    options.streaming
+        || std::fs::metadata(path)
+            .map(|m| m.len() > STREAM_THRESHOLD_BYTES)
+            .unwrap_or(false)
+}
+
+/// 大文件自动改用流式导入时记录警告，说明哪些导入选项不生效
+fn warn_auto_streaming(options: &ExcelOptions, log: &mut ProcessLog) {
+    if !options.streaming {
+        log.warn(format!(
+            "文件超过 {} MB，已自动改用流式导入：合并单元格、分块拆分、公式求值、空行列裁剪和自定义数字格式不生效",
+            STREAM_THRESHOLD_BYTES / 1024 / 1024
+        ));
+    }
+}
+
+/// 流式处理 Excel 文件
+///
+/// 先保存标记为未完成的文件记录（读取时不可见），再按批读取工作表，每批写入一条表格记录，
+/// 只在写入期间占用数据库连接；全部写入后在同一事务中删除被替换的重复文件并启用文件记录。
+/// 取消或出错时删除已写入的文件记录和表格记录，进程中途退出遗留的记录在下次启动时删除
+fn process_excel_streaming(
+    path: &str,
+    person_id: Option<i32>,
+    origin: Origin,
+    ctx: &ProcessContext,
+    log: &mut ProcessLog,
+) -> Result<ProcessResult, String> {
+    // 读取和写入交替进行，整体计入保存阶段
+    log.stage(ProcessStage::Saving);
+    warn_auto_streaming(&ctx.options.excel, log);
+    let stored = store_original(path, origin.content_hash)?;
+
+    let file_id = add_incomplete_record(ctx, &stored, "excel", person_id, origin)?;
+
+    let mut results = Vec::new();
+    let mut rows = 0;
+
+    let streamed = ocr::excel_stream::ExcelStreamer::stream_tables(path, &ctx.options.excel, STREAM_BATCH_ROWS, |table| {
+        ctx.check_cancelled()?;
+
+        // 总行数未知，按已写入的行数报告
+        rows += table.rows.len();
+        ctx.report(path, ProcessStage::Saving, rows, 0);
+
+        let content = to_string(&table).map_err(|e| e.to_string())?;
+        let conn = ctx.state.get_conn()?;
+        let record_id = db::add_table_record(&conn, file_id, person_id, &content).map_err(|e| e.to_string())?;
+
+        results.push(TableResult::new(results.len(), record_id, &table));
+
+        Ok(())
+    })
+    .and_then(|summary| complete_file(ctx, file_id, origin.replaces).map(|_| summary));
+
+    match streamed {
+        Ok(summary) => summary.warnings.into_iter().for_each(|warning| log.warn(warning)),
+        Err(e) => {
+            let _ = delete_files(ctx, &[file_id]);
+            return Err(e);
+        }
+    }
+
+    Ok(ProcessResult {
+        file_id,
+        tables: results,
+        ..Default::default()
+    })
+}
+
+/// 从路径提取文件名
+fn file_name(path: &str) -> &str {
+    std::path::Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+}
+
+/// 文件提取选项（按文件类型分组）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProcessOptions {
+    pub excel: ExcelOptions,           // Excel 导入选项
+    pub duplicate: DuplicatePolicy,    // 已导入过相同内容的文件时的处理方式
+}
+
+/// 重复文件（内容哈希相同）的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    #[default]
+    Skip,      // 不再处理，返回已有的文件记录
+    Replace,   // 删除已有的文件记录（含子文件和表格记录）后重新处理
+    KeepBoth,  // 保留已有记录，另存一份
+}
+
+/// 重新处理的保存方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReprocessMode {
+    #[default]
+    Replace,     // 替换当前版本的表格记录
+    NewVersion,  // 保留当前版本，另存为新版本
+}
+
+/// 重新处理选项
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ReprocessOptions {
+    pub mode: ReprocessMode,
+    pub keep_edits: bool,  // 是否保留手动修改过的表格
+}
+
+impl Default for ReprocessOptions {
+    fn default() -> Self {
+        Self {
+            mode: ReprocessMode::Replace,
+            keep_edits: true,
+        }
+    }
+}
+
+/// 重新处理结果
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReprocessResult {
+    pub file_id: i32,
+    pub version: i32,        // 保存的版本号
+    pub table_count: usize,  // 保存的表格数（含保留的手动修改）
+    pub kept_edits: usize,   // 保留的手动修改表格数
+    pub unmatched_edits: usize,  // 找不到对应表格、未保留的手动修改表格数
+}
+
 /// 处理结果结构体
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ProcessResult {
     pub file_id: i32,       // 保存的文件记录 ID
+    pub file_name: String,  // 文件名（邮件附件、压缩包成员为子文件名）
     pub tables: Vec<TableResult>,  // 处理出的表格信息
+    pub children: Vec<ProcessResult>,  // 子文件（邮件附件、压缩包成员）的处理结果
+    pub errors: Vec<String>,  // 子文件处理失败信息
+    pub duplicate_of: Option<i32>,  // 内容重复时已有的文件记录 ID（跳过处理时与 file_id 相同）
+}
+
+impl ProcessResult {
+    /// 表格总数（含子文件）
+    pub fn table_count(&self) -> usize {
+        self.tables.len() + self.children.iter().map(|c| c.table_count()).sum::<usize>()
+    }
+
+    /// 所有处理失败信息（含子文件）
+    pub fn all_errors(&self) -> Vec<String> {
+        let mut errors = self.errors.clone();
+        for child in &self.children {
+            errors.extend(child.all_errors());
+        }
+        errors
+    }
 }
 
 /// 单个表格结果信息
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TableResult {
     pub index: u32,     // 表格索引
+    pub record_id: i32, // 保存的表格记录 ID
     pub rows: usize,    // 行数
     pub cols: usize,    // 列数
+    pub source: TableSource,              // 在源文件中的位置（页、工作表、幻灯片）
+    pub headers: Option<Vec<String>>,     // 识别出的表头
+    pub preview: Vec<Vec<String>>,        // 前几行数据（含表头行）
+    pub confidence: Option<f32>,          // 识别置信度（仅 OCR 来源）
+}
+
+impl TableResult {
+    fn new(index: usize, record_id: i32, table: &ExtractedTable) -> Self {
+        Self {
+            index: index as u32,
+            record_id,
+            rows: table.rows.len(),
+            cols: table.rows.first().map(|r| r.len()).unwrap_or(0),
+            source: table.source.clone(),
+            headers: table.detect_headers(),
+            preview: table.rows.iter().take(PREVIEW_ROWS).cloned().collect(),
+            confidence: table.confidence,
+        }
+    }
 }
 
 /// 获取文件的表格记录
@@ -134,3 +1183,53 @@ pub async fn get_file_table_records(
     let records = db::get_table_records(&conn, file_id)?;
     Ok(records)
 }
+
+/// 处理日志（阶段耗时和警告已解析）
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessingLogInfo {
+    pub id: i32,
+    pub file_id: Option<i32>,
+    pub file_path: String,
+    pub status: String,               // completed、failed、cancelled
+    pub stages: Vec<StageTiming>,     // 各阶段耗时
+    pub warnings: Vec<String>,        // 如无文本层的页、低置信度、子文件处理失败
+    pub error: Option<String>,
+    pub duration_ms: i64,
+    pub created_at: String,
+}
+
+impl From<db::ProcessingLog> for ProcessingLogInfo {
+    fn from(log: db::ProcessingLog) -> Self {
+        Self {
+            id: log.id,
+            file_id: log.file_id,
+            file_path: log.file_path,
+            status: log.status,
+            stages: serde_json::from_str(&log.stages).unwrap_or_default(),
+            warnings: serde_json::from_str(&log.warnings).unwrap_or_default(),
+            error: log.error,
+            duration_ms: log.duration_ms,
+            created_at: log.created_at,
+        }
+    }
+}
+
+/// 获取处理日志
+///
+/// # 参数
+/// * `file_id` - 文件记录 ID，未提供时返回最近的处理日志（含未保存文件记录的失败处理）
+#[tauri::command]
+pub async fn get_processing_logs(
+    file_id: Option<i32>,
+    state: State<'_, DbState>,
+) -> Result<Vec<ProcessingLogInfo>, String> {
+    let conn = state.get_conn()?;
+    let logs = match file_id {
+        Some(file_id) => db::get_processing_logs_by_file(&conn, file_id),
+        None => db::get_recent_processing_logs(&conn, MAX_LOGS),
+    }
+    .map_err(|e| e.to_string())?;
+
+    Ok(logs.into_iter().map(ProcessingLogInfo::from).collect())
+}
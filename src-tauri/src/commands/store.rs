@@ -0,0 +1,171 @@
+//! 文档存储 Tauri 命令模块
+//! 导入的原始文件按内容哈希复制到应用存储目录，原文件移动或删除后记录仍然可用；
+//! 提供打开、导出存储副本和清理未引用文件的命令
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use serde::Serialize;
+use tauri::State;
+use crate::commands::person::DbState;
+use crate::commands::process;
+use crate::db::{self, FileRecord};
+
+/// 存储目录（与数据库同在工作目录下）
+pub const STORE_DIR: &str = "store";
+
+/// 最近写入或复用的副本在清理时保留的时长
+///
+/// 处理任务先复制文件再写入记录，两步之间副本还没有被引用
+const CLEANUP_GRACE: Duration = Duration::from_secs(10 * 60);
+
+/// 清理结果
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreCleanup {
+    pub removed: usize,     // 删除的文件数
+    pub freed_bytes: u64,   // 释放的字节数
+}
+
+/// 将文件复制到存储目录，内容相同的文件只保存一份
+///
+/// # 参数
+/// * `file_path` - 原始文件路径
+/// * `content_hash` - 文件内容的 SHA-256
+///
+/// # 返回
+/// 存储副本的路径：`store/<哈希前两位>/<哈希>.<扩展名>`
+pub fn store_file(file_path: &str, content_hash: &str) -> Result<PathBuf, String> {
+    // 保留扩展名，方便用系统默认程序打开
+    let ext = Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .filter(|e| !e.is_empty() && e.chars().all(|c| c.is_ascii_alphanumeric()));
+    let name = match ext {
+        Some(ext) => format!("{}.{}", content_hash, ext),
+        None => content_hash.to_string(),
+    };
+
+    let dir = Path::new(STORE_DIR).join(&content_hash[..2]);
+    let target = dir.join(name);
+
+    // 已有副本时更新修改时间，避免在写入记录前被清理
+    if touch(&target).is_ok() {
+        return Ok(target);
+    }
+
+    // 先写入临时文件再改名，中途失败不会留下不完整的副本
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let temp = target.with_extension("tmp");
+    std::fs::copy(file_path, &temp).map_err(|e| format!("复制文件到存储目录失败: {}", e))?;
+    std::fs::rename(&temp, &target).map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        e.to_string()
+    })?;
+
+    // 部分系统复制时保留原文件的修改时间
+    let _ = touch(&target);
+    Ok(target)
+}
+
+/// 将已存在文件的修改时间设为当前时间
+fn touch(path: &Path) -> std::io::Result<()> {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+/// 获取文件记录及其存储副本路径
+fn stored_file(state: &DbState, id: i32) -> Result<(FileRecord, PathBuf), String> {
+    let conn = state.get_conn()?;
+    let file = db::get_file(&conn, id).map_err(|e| e.to_string())?;
+    let path = PathBuf::from(&file.file_path);
+    if !path.is_file() {
+        return Err(format!("文件不存在: {}", file.file_path));
+    }
+    Ok((file, path))
+}
+
+/// 用系统默认程序打开存储的原始文件
+#[tauri::command]
+pub fn open_stored_file(id: i32, state: State<DbState>) -> Result<(), String> {
+    let (_, path) = stored_file(&state, id)?;
+
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    command.arg(&path).spawn().map_err(|e| format!("打开文件失败: {}", e))?;
+    Ok(())
+}
+
+/// 导出存储的原始文件
+///
+/// # 参数
+/// * `id` - 文件记录 ID
+/// * `target_path` - 导出路径，为文件夹时使用原文件名
+///
+/// # 返回
+/// 导出后的文件路径
+#[tauri::command]
+pub fn export_stored_file(id: i32, target_path: String, state: State<DbState>) -> Result<String, String> {
+    let (file, path) = stored_file(&state, id)?;
+
+    let mut target = PathBuf::from(&target_path);
+    if target.is_dir() {
+        target = process::unique_path(&target, &file.file_name);
+    }
+
+    std::fs::copy(&path, &target).map_err(|e| format!("导出文件失败: {}", e))?;
+    Ok(target.to_string_lossy().into_owned())
+}
+
+/// 删除存储目录中没有文件记录引用的副本
+#[tauri::command]
+pub fn cleanup_store(state: State<DbState>) -> Result<StoreCleanup, String> {
+    let conn = state.get_conn()?;
+    let referenced: HashSet<PathBuf> = db::get_all_file_paths(&conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+
+    let mut cleanup = StoreCleanup::default();
+    let Ok(dirs) = std::fs::read_dir(STORE_DIR) else { return Ok(cleanup) };
+
+    for dir in dirs.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_file()) {
+            if referenced.contains(&path) {
+                continue;
+            }
+            let Ok(metadata) = std::fs::metadata(&path) else { continue };
+            let recent = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.elapsed().ok())
+                .is_none_or(|age| age < CLEANUP_GRACE);
+            if recent {
+                continue;
+            }
+            let size = metadata.len();
+            if std::fs::remove_file(&path).is_ok() {
+                cleanup.removed += 1;
+                cleanup.freed_bytes += size;
+            }
+        }
+        // 只删除空文件夹
+        let _ = std::fs::remove_dir(&dir);
+    }
+
+    Ok(cleanup)
+}
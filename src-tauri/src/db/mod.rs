@@ -18,9 +18,28 @@ pub struct FileRecord {
     pub file_name: String,
     pub file_path: String,
     pub file_type: String,
+    pub parent_id: Option<i32>,  // 来源文件 ID（邮件附件、压缩包成员等），顶层文件为空
+    pub member_path: Option<String>,  // 在来源文件中的路径（压缩包内路径或附件名）
+    pub content_hash: Option<String>, // 文件内容的 SHA-256（十六进制）
+    pub original_path: Option<String>, // 导入时的原始路径（file_path 为应用存储中的副本）
     pub created_at: String,
 }
 
+// 处理任务结构体
+#[derive(Debug, Serialize, Clone)]
+pub struct JobRecord {
+    pub id: i32,
+    pub file_path: String,
+    pub file_type: Option<String>,  // 调用方指定的文件类型，为空时按内容识别
+    pub person_id: Option<i32>,
+    pub options: String,            // 提取选项（JSON）
+    pub status: String,             // queued / running / completed / failed / cancelled
+    pub file_id: Option<i32>,       // 完成后保存的文件记录 ID
+    pub error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
 // 表格记录结构体
 #[derive(Debug, Serialize, Clone)]
 pub struct TableRecord {
@@ -28,6 +47,8 @@ pub struct TableRecord {
     pub file_id: i32,
     pub person_id: Option<i32>,
     pub content: String,
+    pub version: i32,    // 提取版本，重新处理并另存为新版本时递增
+    pub edited: bool,    // 是否被手动修改过
     pub created_at: String,
 }
 
@@ -54,12 +75,26 @@ pub fn init_db() -> Result<Connection> {
             file_name TEXT NOT NULL,
             file_path TEXT NOT NULL,
             file_type TEXT NOT NULL,
+            parent_id INTEGER,
+            member_path TEXT,
+            content_hash TEXT,
+            original_path TEXT,
+            incomplete INTEGER NOT NULL DEFAULT 0,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (person_id) REFERENCES persons(id)
+            FOREIGN KEY (person_id) REFERENCES persons(id),
+            FOREIGN KEY (parent_id) REFERENCES files(id)
         )",
         [],
     )?;
 
+    // 旧版本数据库的文件表缺少 parent_id 列
+    ensure_column(&conn, "files", "parent_id", "INTEGER REFERENCES files(id)")?;
+    ensure_column(&conn, "files", "member_path", "TEXT")?;
+    ensure_column(&conn, "files", "content_hash", "TEXT")?;
+    ensure_column(&conn, "files", "original_path", "TEXT")?;
+    ensure_column(&conn, "files", "incomplete", "INTEGER NOT NULL DEFAULT 0")?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_files_content_hash ON files(content_hash)", [])?;
+
     // 创建表格记录表
     conn.execute(
         "CREATE TABLE IF NOT EXISTS table_records (
@@ -67,6 +102,8 @@ pub fn init_db() -> Result<Connection> {
             file_id INTEGER NOT NULL,
             person_id INTEGER,
             content TEXT NOT NULL,
+            version INTEGER NOT NULL DEFAULT 1,
+            edited INTEGER NOT NULL DEFAULT 0,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             FOREIGN KEY (file_id) REFERENCES files(id),
             FOREIGN KEY (person_id) REFERENCES persons(id)
@@ -74,9 +111,74 @@ pub fn init_db() -> Result<Connection> {
         [],
     )?;
 
+    ensure_column(&conn, "table_records", "version", "INTEGER NOT NULL DEFAULT 1")?;
+    ensure_column(&conn, "table_records", "edited", "INTEGER NOT NULL DEFAULT 0")?;
+
+    // 创建处理任务表
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS processing_jobs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_path TEXT NOT NULL,
+            file_type TEXT,
+            person_id INTEGER,
+            options TEXT NOT NULL DEFAULT '{}',
+            status TEXT NOT NULL,
+            file_id INTEGER,
+            error TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (person_id) REFERENCES persons(id),
+            FOREIGN KEY (file_id) REFERENCES files(id)
+        )",
+        [],
+    )?;
+
+    // 创建处理日志表（每次处理一个文件一条，阶段耗时和警告为 JSON 数组）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS processing_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER,
+            file_path TEXT NOT NULL,
+            status TEXT NOT NULL,
+            stages TEXT NOT NULL DEFAULT '[]',
+            warnings TEXT NOT NULL DEFAULT '[]',
+            error TEXT,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (file_id) REFERENCES files(id)
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_processing_logs_file ON processing_logs(file_id)", [])?;
+
+    // 创建设置表（键值对，值为 JSON）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
     Ok(conn)
 }
 
+/// 为已存在的表补充新增的列（用于旧版本数据库升级）
+fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<String>>>()?
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(column));
+
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
 // 人员相关操作
 pub fn add_person(conn: &Connection, name: &str) -> Result<i32> {
     let mut stmt = conn.prepare("INSERT INTO persons (name) VALUES (?1)")?;
@@ -117,86 +219,231 @@ pub fn add_file(
     file_name: &str,
     file_path: &str,
     file_type: &str,
+) -> Result<i32> {
+    add_child_file(conn, person_id, None, None, file_name, file_path, file_type)
+}
+
+/// 添加文件记录，并关联到来源文件（如邮件附件关联到邮件、压缩包成员关联到压缩包）
+pub fn add_child_file(
+    conn: &Connection,
+    person_id: Option<i32>,
+    parent_id: Option<i32>,
+    member_path: Option<&str>,
+    file_name: &str,
+    file_path: &str,
+    file_type: &str,
 ) -> Result<i32> {
     let mut stmt = conn.prepare(
-        "INSERT INTO files (person_id, file_name, file_path, file_type) VALUES (?1, ?2, ?3, ?4)",
+        "INSERT INTO files (person_id, file_name, file_path, file_type, parent_id, member_path) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
     )?;
-    stmt.execute(params![person_id, file_name, file_path, file_type])?;
+    stmt.execute(params![person_id, file_name, file_path, file_type, parent_id, member_path])?;
     Ok(conn.last_insert_rowid() as i32)
 }
 
+const FILE_COLUMNS: &str =
+    "id, person_id, file_name, file_path, file_type, parent_id, member_path, content_hash, original_path, created_at";
+
+/// 只取已完成导入的文件（流式导入期间文件记录标记为未完成，读取时不可见）
+const COMPLETE_FILE: &str = "incomplete = 0";
+
+fn file_from_row(row: &rusqlite::Row) -> Result<FileRecord> {
+    Ok(FileRecord {
+        id: row.get(0)?,
+        person_id: row.get(1)?,
+        file_name: row.get(2)?,
+        file_path: row.get(3)?,
+        file_type: row.get(4)?,
+        parent_id: row.get(5)?,
+        member_path: row.get(6)?,
+        content_hash: row.get(7)?,
+        original_path: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
 pub fn get_files(conn: &Connection) -> Result<Vec<FileRecord>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, person_id, file_name, file_path, file_type, created_at FROM files ORDER BY created_at DESC",
-    )?;
-    let files = stmt.query_map([], |row| {
-        Ok(FileRecord {
-            id: row.get(0)?,
-            person_id: row.get(1)?,
-            file_name: row.get(2)?,
-            file_path: row.get(3)?,
-            file_type: row.get(4)?,
-            created_at: row.get(5)?,
-        })
-    })?.collect::<Result<Vec<FileRecord>>>()?;
+    let sql = format!("SELECT {} FROM files WHERE {} ORDER BY created_at DESC", FILE_COLUMNS, COMPLETE_FILE);
+    let mut stmt = conn.prepare(&sql)?;
+    let files = stmt.query_map([], file_from_row)?.collect::<Result<Vec<FileRecord>>>()?;
     Ok(files)
 }
 
-pub fn delete_file(conn: &Connection, id: i32) -> Result<()> {
-    conn.execute("DELETE FROM files WHERE id = ?1", [&id.to_string()])?;
+pub fn get_file(conn: &Connection, id: i32) -> Result<FileRecord> {
+    let sql = format!("SELECT {} FROM files WHERE id = ?1", FILE_COLUMNS);
+    conn.query_row(&sql, [id], file_from_row)
+}
+
+/// 获取所有文件路径（用于清理存储中未被引用的文件）
+pub fn get_all_file_paths(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT file_path FROM files")?;
+    let paths = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<String>>>()?;
+    Ok(paths)
+}
+
+/// 获取来源文件下的所有子文件（如邮件的附件）
+pub fn get_child_files(conn: &Connection, parent_id: i32) -> Result<Vec<FileRecord>> {
+    let sql = format!("SELECT {} FROM files WHERE parent_id = ?1 AND {} ORDER BY id", FILE_COLUMNS, COMPLETE_FILE);
+    let mut stmt = conn.prepare(&sql)?;
+    let files = stmt.query_map([parent_id], file_from_row)?.collect::<Result<Vec<FileRecord>>>()?;
+    Ok(files)
+}
+
+/// 按内容哈希查找文件（按添加顺序）
+pub fn get_files_by_hash(conn: &Connection, content_hash: &str) -> Result<Vec<FileRecord>> {
+    let sql = format!("SELECT {} FROM files WHERE content_hash = ?1 AND {} ORDER BY id", FILE_COLUMNS, COMPLETE_FILE);
+    let mut stmt = conn.prepare(&sql)?;
+    let files = stmt.query_map([content_hash], file_from_row)?.collect::<Result<Vec<FileRecord>>>()?;
+    Ok(files)
+}
+
+/// 获取内容重复的文件（同一哈希有多条记录），按哈希分组、组内按添加顺序排列
+pub fn get_duplicate_files(conn: &Connection) -> Result<Vec<FileRecord>> {
+    let sql = format!(
+        "SELECT {0} FROM files WHERE {1} AND content_hash IN (
+            SELECT content_hash FROM files WHERE content_hash IS NOT NULL AND {1}
+            GROUP BY content_hash HAVING COUNT(*) > 1
+        ) ORDER BY content_hash, id",
+        FILE_COLUMNS, COMPLETE_FILE
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let files = stmt.query_map([], file_from_row)?.collect::<Result<Vec<FileRecord>>>()?;
+    Ok(files)
+}
+
+/// 保存文件内容哈希
+pub fn set_file_hash(conn: &Connection, id: i32, content_hash: &str) -> Result<()> {
+    conn.execute("UPDATE files SET content_hash = ?1 WHERE id = ?2", params![content_hash, id])?;
+    Ok(())
+}
+
+/// 文件复制到应用存储后，将路径指向副本并保留原始路径
+pub fn set_stored_path(conn: &Connection, id: i32, stored_path: &str, original_path: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE files SET file_path = ?1, original_path = ?2 WHERE id = ?3",
+        params![stored_path, original_path, id],
+    )?;
+    Ok(())
+}
+
+/// 删除文件及其所有子文件（附件、压缩包成员）和它们的表格记录、处理日志，并解除处理任务的关联
+pub fn delete_file_tree(conn: &Connection, id: i32) -> Result<()> {
+    const TREE: &str = "WITH RECURSIVE tree(id) AS (
+        SELECT ?1 UNION ALL SELECT f.id FROM files f JOIN tree t ON f.parent_id = t.id
+    )";
+    conn.execute(
+        &format!("{} DELETE FROM table_records WHERE file_id IN (SELECT id FROM tree)", TREE),
+        [id],
+    )?;
+    conn.execute(
+        &format!("{} DELETE FROM processing_logs WHERE file_id IN (SELECT id FROM tree)", TREE),
+        [id],
+    )?;
+    conn.execute(
+        &format!("{} UPDATE processing_jobs SET file_id = NULL WHERE file_id IN (SELECT id FROM tree)", TREE),
+        [id],
+    )?;
+    conn.execute(&format!("{} DELETE FROM files WHERE id IN (SELECT id FROM tree)", TREE), [id])?;
+    Ok(())
+}
+
+/// 标记文件记录是否未完成导入
+pub fn set_file_incomplete(conn: &Connection, id: i32, incomplete: bool) -> Result<()> {
+    conn.execute("UPDATE files SET incomplete = ?1 WHERE id = ?2", params![incomplete, id])?;
+    Ok(())
+}
+
+/// 删除上次退出时未完成导入的文件记录（含子文件和表格记录），返回删除的文件数
+pub fn delete_incomplete_files(conn: &Connection) -> Result<usize> {
+    let mut stmt = conn.prepare("SELECT id FROM files WHERE incomplete = 1")?;
+    let ids = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<i32>>>()?;
+    for id in &ids {
+        delete_file_tree(conn, *id)?;
+    }
+    Ok(ids.len())
+}
+
+/// 原始文件被移动后更新原始路径（仅当记录仍指向移动前的路径时）
+pub fn move_original_path(conn: &Connection, id: i32, from: &str, to: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE files SET original_path = ?1 WHERE id = ?2 AND original_path = ?3",
+        params![to, id, from],
+    )?;
     Ok(())
 }
 
 // 表格记录相关操作
 
-/// 获取指定文件的所有表格记录
+const RECORD_COLUMNS: &str = "id, file_id, person_id, content, version, edited, created_at";
+
+/// 只取每个文件最新版本的表格记录
+///
+/// 版本号为负数的记录是重新处理时尚未启用的临时记录，未完成导入的文件的记录同样不可见
+const LATEST_VERSION: &str = "version = (SELECT MAX(version) FROM table_records latest \
+     WHERE latest.file_id = table_records.file_id AND latest.version > 0) \
+     AND file_id IN (SELECT id FROM files WHERE incomplete = 0)";
+
+fn record_from_row(row: &rusqlite::Row) -> Result<TableRecord> {
+    Ok(TableRecord {
+        id: row.get(0)?,
+        file_id: row.get(1)?,
+        person_id: row.get(2)?,
+        content: row.get(3)?,
+        version: row.get(4)?,
+        edited: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// 获取指定文件的所有表格记录（最新版本）
 pub fn get_table_records_by_file(conn: &Connection, file_id: i32) -> Result<Vec<TableRecord>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, file_id, person_id, content, created_at FROM table_records WHERE file_id = ?1 ORDER BY created_at DESC",
-    )?;
-    let records = stmt.query_map([file_id], |row| {
-        Ok(TableRecord {
-            id: row.get(0)?,
-            file_id: row.get(1)?,
-            person_id: row.get(2)?,
-            content: row.get(3)?,
-            created_at: row.get(4)?,
-        })
-    })?.collect::<Result<Vec<TableRecord>>>()?;
+    let sql = format!(
+        "SELECT {} FROM table_records WHERE file_id = ?1 AND {} ORDER BY created_at DESC",
+        RECORD_COLUMNS, LATEST_VERSION
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let records = stmt.query_map([file_id], record_from_row)?.collect::<Result<Vec<TableRecord>>>()?;
     Ok(records)
 }
 
-/// 获取指定人员的所有表格记录
+/// 获取指定文件某个版本的表格记录（按提取顺序）
+pub fn get_table_records_by_version(conn: &Connection, file_id: i32, version: i32) -> Result<Vec<TableRecord>> {
+    let sql = format!(
+        "SELECT {} FROM table_records WHERE file_id = ?1 AND version = ?2 ORDER BY id",
+        RECORD_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let records = stmt.query_map(params![file_id, version], record_from_row)?.collect::<Result<Vec<TableRecord>>>()?;
+    Ok(records)
+}
+
+/// 获取文件表格记录的最新版本号，没有记录时为 1
+pub fn get_latest_version(conn: &Connection, file_id: i32) -> Result<i32> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 1) FROM table_records WHERE file_id = ?1 AND version > 0",
+        [file_id],
+        |row| row.get(0),
+    )
+}
+
+/// 获取指定人员的所有表格记录（最新版本）
 pub fn get_table_records_by_person(conn: &Connection, person_id: i32) -> Result<Vec<TableRecord>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, file_id, person_id, content, created_at FROM table_records WHERE person_id = ?1 ORDER BY created_at DESC",
-    )?;
-    let records = stmt.query_map([person_id], |row| {
-        Ok(TableRecord {
-            id: row.get(0)?,
-            file_id: row.get(1)?,
-            person_id: row.get(2)?,
-            content: row.get(3)?,
-            created_at: row.get(4)?,
-        })
-    })?.collect::<Result<Vec<TableRecord>>>()?;
+    let sql = format!(
+        "SELECT {} FROM table_records WHERE person_id = ?1 AND {} ORDER BY created_at DESC",
+        RECORD_COLUMNS, LATEST_VERSION
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let records = stmt.query_map([person_id], record_from_row)?.collect::<Result<Vec<TableRecord>>>()?;
     Ok(records)
 }
 
-/// 获取所有表格记录
+/// 获取所有表格记录（最新版本）
 pub fn get_all_table_records(conn: &Connection) -> Result<Vec<TableRecord>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, file_id, person_id, content, created_at FROM table_records ORDER BY created_at DESC",
-    )?;
-    let records = stmt.query_map([], |row| {
-        Ok(TableRecord {
-            id: row.get(0)?,
-            file_id: row.get(1)?,
-            person_id: row.get(2)?,
-            content: row.get(3)?,
-            created_at: row.get(4)?,
-        })
-    })?.collect::<Result<Vec<TableRecord>>>()?;
+    let sql = format!(
+        "SELECT {} FROM table_records WHERE {} ORDER BY created_at DESC",
+        RECORD_COLUMNS, LATEST_VERSION
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let records = stmt.query_map([], record_from_row)?.collect::<Result<Vec<TableRecord>>>()?;
     Ok(records)
 }
 
@@ -212,17 +459,53 @@ pub fn add_table_record(
     person_id: Option<i32>,
     content: &str,
 ) -> Result<i32> {
-    let mut stmt = conn.prepare(
-        "INSERT INTO table_records (file_id, person_id, content) VALUES (?1, ?2, ?3)",
+    add_versioned_table_record(conn, file_id, person_id, content, 1, false)
+}
+
+/// 添加指定版本的表格记录（重新处理时使用）
+pub fn add_versioned_table_record(
+    conn: &Connection,
+    file_id: i32,
+    person_id: Option<i32>,
+    content: &str,
+    version: i32,
+    edited: bool,
+) -> Result<i32> {
+    // 批量写入时复用已编译的语句
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO table_records (file_id, person_id, content, version, edited) VALUES (?1, ?2, ?3, ?4, ?5)",
     )?;
-    stmt.execute(params![file_id, person_id, content])?;
+    stmt.execute(params![file_id, person_id, content, version, edited])?;
     Ok(conn.last_insert_rowid() as i32)
 }
 
-/// 更新表格记录内容
+/// 删除文件某个版本的表格记录
+pub fn delete_table_records_by_version(conn: &Connection, file_id: i32, version: i32) -> Result<()> {
+    conn.execute(
+        "DELETE FROM table_records WHERE file_id = ?1 AND version = ?2",
+        params![file_id, version],
+    )?;
+    Ok(())
+}
+
+/// 将文件某个版本的表格记录改为另一个版本号
+pub fn set_table_records_version(conn: &Connection, file_id: i32, from: i32, to: i32) -> Result<()> {
+    conn.execute(
+        "UPDATE table_records SET version = ?3 WHERE file_id = ?1 AND version = ?2",
+        params![file_id, from, to],
+    )?;
+    Ok(())
+}
+
+/// 删除重新处理中断后遗留的临时记录（版本号为负数）
+pub fn delete_staging_table_records(conn: &Connection) -> Result<usize> {
+    conn.execute("DELETE FROM table_records WHERE version < 0", [])
+}
+
+/// 更新表格记录内容（标记为手动修改）
 pub fn update_table_record(conn: &Connection, id: i32, content: &str) -> Result<()> {
     conn.execute(
-        "UPDATE table_records SET content = ?1 WHERE id = ?2",
+        "UPDATE table_records SET content = ?1, edited = 1 WHERE id = ?2",
         [content, &id.to_string()],
     )?;
     Ok(())
@@ -233,3 +516,183 @@ pub fn delete_table_record(conn: &Connection, id: i32) -> Result<()> {
     conn.execute("DELETE FROM table_records WHERE id = ?1", [&id.to_string()])?;
     Ok(())
 }
+
+// 处理日志相关操作
+
+/// 处理日志记录
+#[derive(Debug, Serialize, Clone)]
+pub struct ProcessingLog {
+    pub id: i32,
+    pub file_id: Option<i32>,   // 处理失败且未保存文件记录时为空
+    pub file_path: String,
+    pub status: String,         // completed、failed、cancelled
+    pub stages: String,         // 各阶段耗时（JSON）
+    pub warnings: String,       // 警告信息（JSON）
+    pub error: Option<String>,
+    pub duration_ms: i64,
+    pub created_at: String,
+}
+
+const LOG_COLUMNS: &str =
+    "id, file_id, file_path, status, stages, warnings, error, duration_ms, created_at";
+
+fn log_from_row(row: &rusqlite::Row) -> Result<ProcessingLog> {
+    Ok(ProcessingLog {
+        id: row.get(0)?,
+        file_id: row.get(1)?,
+        file_path: row.get(2)?,
+        status: row.get(3)?,
+        stages: row.get(4)?,
+        warnings: row.get(5)?,
+        error: row.get(6)?,
+        duration_ms: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+/// 添加处理日志
+#[allow(clippy::too_many_arguments)]
+pub fn add_processing_log(
+    conn: &Connection,
+    file_id: Option<i32>,
+    file_path: &str,
+    status: &str,
+    stages: &str,
+    warnings: &str,
+    error: Option<&str>,
+    duration_ms: i64,
+) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO processing_logs (file_id, file_path, status, stages, warnings, error, duration_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![file_id, file_path, status, stages, warnings, error, duration_ms],
+    )?;
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+/// 获取文件的处理日志（最新的在前）
+pub fn get_processing_logs_by_file(conn: &Connection, file_id: i32) -> Result<Vec<ProcessingLog>> {
+    let sql = format!("SELECT {} FROM processing_logs WHERE file_id = ?1 ORDER BY id DESC", LOG_COLUMNS);
+    let mut stmt = conn.prepare(&sql)?;
+    let logs = stmt.query_map([file_id], log_from_row)?.collect::<Result<Vec<ProcessingLog>>>()?;
+    Ok(logs)
+}
+
+/// 获取指定路径最近一次的处理日志
+pub fn get_latest_processing_log_by_path(conn: &Connection, file_path: &str) -> Result<Option<ProcessingLog>> {
+    let sql = format!("SELECT {} FROM processing_logs WHERE file_path = ?1 ORDER BY id DESC LIMIT 1", LOG_COLUMNS);
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query([file_path])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(log_from_row(row)?)),
+        None => Ok(None),
+    }
+}
+
+/// 获取最近的处理日志
+pub fn get_recent_processing_logs(conn: &Connection, limit: u32) -> Result<Vec<ProcessingLog>> {
+    let sql = format!("SELECT {} FROM processing_logs ORDER BY id DESC LIMIT ?1", LOG_COLUMNS);
+    let mut stmt = conn.prepare(&sql)?;
+    let logs = stmt.query_map([limit], log_from_row)?.collect::<Result<Vec<ProcessingLog>>>()?;
+    Ok(logs)
+}
+
+// 处理任务相关操作
+
+const JOB_COLUMNS: &str =
+    "id, file_path, file_type, person_id, options, status, file_id, error, created_at, updated_at";
+
+fn job_from_row(row: &rusqlite::Row) -> Result<JobRecord> {
+    Ok(JobRecord {
+        id: row.get(0)?,
+        file_path: row.get(1)?,
+        file_type: row.get(2)?,
+        person_id: row.get(3)?,
+        options: row.get(4)?,
+        status: row.get(5)?,
+        file_id: row.get(6)?,
+        error: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+/// 添加排队中的处理任务
+pub fn add_job(
+    conn: &Connection,
+    file_path: &str,
+    file_type: Option<&str>,
+    person_id: Option<i32>,
+    options: &str,
+) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO processing_jobs (file_path, file_type, person_id, options, status) VALUES (?1, ?2, ?3, ?4, 'queued')",
+        params![file_path, file_type, person_id, options],
+    )?;
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+/// 更新任务状态、结果文件和错误信息
+pub fn update_job_status(
+    conn: &Connection,
+    id: i32,
+    status: &str,
+    file_id: Option<i32>,
+    error: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE processing_jobs SET status = ?1, file_id = ?2, error = ?3, updated_at = CURRENT_TIMESTAMP WHERE id = ?4",
+        params![status, file_id, error, id],
+    )?;
+    Ok(())
+}
+
+/// 更新任务的文件路径（文件被移动后，重试时使用新路径）
+pub fn update_job_file_path(conn: &Connection, id: i32, file_path: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE processing_jobs SET file_path = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+        params![file_path, id],
+    )?;
+    Ok(())
+}
+
+pub fn get_job(conn: &Connection, id: i32) -> Result<JobRecord> {
+    let sql = format!("SELECT {} FROM processing_jobs WHERE id = ?1", JOB_COLUMNS);
+    conn.query_row(&sql, [id], job_from_row)
+}
+
+pub fn get_jobs(conn: &Connection) -> Result<Vec<JobRecord>> {
+    let sql = format!("SELECT {} FROM processing_jobs ORDER BY id", JOB_COLUMNS);
+    let mut stmt = conn.prepare(&sql)?;
+    let jobs = stmt.query_map([], job_from_row)?.collect::<Result<Vec<JobRecord>>>()?;
+    Ok(jobs)
+}
+
+/// 删除已结束（完成、失败、取消）的任务
+pub fn delete_finished_jobs(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "DELETE FROM processing_jobs WHERE status IN ('completed', 'failed', 'cancelled')",
+        [],
+    )?;
+    Ok(())
+}
+
+// 设置相关操作
+
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?1")?;
+    let mut rows = stmt.query([key])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP",
+        params![key, value],
+    )?;
+    Ok(())
+}
@@ -73,11 +73,13 @@ fn add_file(
     db::add_file(&conn, person_id, &file_name, &file_path, &file_type).map_err(|e| e.to_string())
 }
 
-// 删除文件
+// 删除文件（含子文件、表格记录和处理日志）
 #[tauri::command]
 fn delete_file(id: i32, state: State<DbState>) -> Result<(), String> {
     let conn = state.get_conn()?;
-    db::delete_file(&conn, id).map_err(|e| e.to_string())
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    db::delete_file_tree(&tx, id).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
 }
 
 // 获取表格记录
@@ -96,6 +98,12 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         // 共享数据库状态
         .manage(commands::new_db_state(conn))
+        // 启动后台处理任务队列和收件箱监视
+        .setup(|app| {
+            app.manage(commands::job::JobQueue::start(app.handle().clone())?);
+            app.manage(commands::inbox::InboxWatcher::start(app.handle().clone())?);
+            Ok(())
+        })
         // 注册命令处理器
         .invoke_handler(tauri::generate_handler![
             greet,
@@ -115,11 +123,30 @@ fn main() {
             get_files,
             add_file,
             delete_file,
+            commands::file::get_duplicate_groups,
             // 表格命令
             get_table_records,
             // 处理命令
             process::process_file,
             process::get_file_table_records,
+            process::reprocess_file,
+            process::get_processing_logs,
+            // 后台任务命令
+            commands::job::submit_process_job,
+            commands::job::get_process_jobs,
+            commands::job::get_process_job,
+            commands::job::cancel_process_job,
+            commands::job::retry_process_job,
+            commands::job::clear_finished_jobs,
+            // 批量导入命令
+            commands::batch::import_folder,
+            // 收件箱命令
+            commands::inbox::get_inbox_settings,
+            commands::inbox::set_inbox_settings,
+            // 文档存储命令
+            commands::store::open_stored_file,
+            commands::store::export_stored_file,
+            commands::store::cleanup_store,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
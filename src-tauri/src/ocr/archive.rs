@@ -0,0 +1,271 @@
+// 压缩包模块 - 解压 ZIP / 7z 压缩包中的文件
+// 对成员路径做规范化以防止路径穿越，并限制成员数量、解压大小和压缩比以防止压缩炸弹
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// ZIP 文件头标识
+const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+
+/// 空 ZIP 文件（只有目录结束记录）的文件头标识
+const ZIP_EMPTY_SIGNATURE: &[u8] = b"PK\x05\x06";
+
+/// 7z 文件头标识
+const SEVEN_Z_SIGNATURE: &[u8] = &[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C];
+
+/// 压缩包处理器
+pub struct ArchiveProcessor;
+
+/// 解压限制
+#[derive(Debug, Clone)]
+pub struct ArchiveLimits {
+    pub max_entries: usize,     // 最多解压的文件数
+    pub max_entry_size: u64,    // 单个文件解压后的最大字节数
+    pub max_total_size: u64,    // 全部文件解压后的最大字节数
+    pub max_ratio: u64,         // 允许的最大压缩比（ZIP 按单个文件，7z 按整个压缩包）
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            max_entry_size: 512 * 1024 * 1024,
+            max_total_size: 2 * 1024 * 1024 * 1024,
+            max_ratio: 200,
+        }
+    }
+}
+
+/// 解压出的文件
+#[derive(Debug, Clone)]
+pub struct ArchiveMember {
+    pub member_path: String,  // 压缩包内的路径（已规范化，以 / 分隔）
+    pub target: PathBuf,      // 解压后的文件路径
+}
+
+/// 解压过程中的累计用量
+#[derive(Default)]
+struct Usage {
+    entries: usize,
+    total: u64,
+    targets: HashSet<String>,  // 已使用的解压路径（小写，不区分大小写的文件系统上同样不会冲突）
+}
+
+impl ArchiveProcessor {
+    /// 文件内容是否为支持的压缩包格式
+    pub fn is_archive(bytes: &[u8]) -> bool {
+        bytes.starts_with(ZIP_SIGNATURE) || bytes.starts_with(ZIP_EMPTY_SIGNATURE) || bytes.starts_with(SEVEN_Z_SIGNATURE)
+    }
+
+    /// 将压缩包解压到目标目录
+    ///
+    /// # 参数
+    /// * `file_path` - 压缩包路径
+    /// * `dest` - 解压目录，成员按压缩包内的目录结构存放
+    /// * `limits` - 解压限制，超出时返回错误
+    ///
+    /// # 返回
+    /// 解压出的文件列表（跳过目录、符号链接和 macOS 资源文件）
+    pub fn extract(file_path: &str, dest: &Path, limits: &ArchiveLimits) -> Result<Vec<ArchiveMember>, String> {
+        let mut header = [0u8; 6];
+        let read = File::open(file_path)
+            .and_then(|mut f| f.read(&mut header))
+            .map_err(|e| e.to_string())?;
+
+        if header[..read].starts_with(SEVEN_Z_SIGNATURE) {
+            Self::extract_7z(file_path, dest, limits)
+        } else {
+            Self::extract_zip(file_path, dest, limits)
+        }
+    }
+
+    /// 解压 ZIP 压缩包
+    fn extract_zip(file_path: &str, dest: &Path, limits: &ArchiveLimits) -> Result<Vec<ArchiveMember>, String> {
+        let file = File::open(file_path).map_err(|e| e.to_string())?;
+        let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+
+        let mut members = Vec::new();
+        let mut usage = Usage::default();
+
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).map_err(|e| e.to_string())?;
+            if entry.is_dir() || is_symlink(entry.unix_mode()) {
+                continue;
+            }
+
+            let Some(relative) = safe_member_path(entry.name()) else { continue };
+
+            // 声明的大小可以伪造，这里只做提前拒绝，实际大小在写入时再次检查
+            if entry.size() > limits.max_entry_size || usage.total.saturating_add(entry.size()) > limits.max_total_size {
+                return Err(format!("压缩包成员 {} 超过大小限制", entry.name()));
+            }
+            if entry.compressed_size() > 0 && entry.size() / entry.compressed_size() > limits.max_ratio {
+                return Err(format!("压缩包成员 {} 压缩比异常，可能是压缩炸弹", entry.name()));
+            }
+
+            let member = write_member(&mut entry, dest, relative, limits, &mut usage)?;
+            members.push(member);
+        }
+
+        Ok(members)
+    }
+
+    /// 解压 7z 压缩包
+    fn extract_7z(file_path: &str, dest: &Path, limits: &ArchiveLimits) -> Result<Vec<ArchiveMember>, String> {
+        let mut archive = sevenz_rust::SevenZReader::open(file_path, sevenz_rust::Password::empty())
+            .map_err(|e| e.to_string())?;
+
+        // 固实压缩时无法得到单个成员的压缩大小，按整个压缩包的大小限制解压总量
+        let archive_size = std::fs::metadata(file_path).map_err(|e| e.to_string())?.len();
+        let limits = &ArchiveLimits {
+            max_total_size: limits.max_total_size.min(archive_size.saturating_mul(limits.max_ratio)),
+            ..limits.clone()
+        };
+
+        let mut members = Vec::new();
+        let mut usage = Usage::default();
+        let mut failure: Option<String> = None;
+
+        archive
+            .for_each_entries(|entry, reader| {
+                let relative = if entry.is_directory() { None } else { safe_member_path(entry.name()) };
+
+                let Some(relative) = relative else {
+                    // 固实压缩的数据需要按顺序读完才能继续读取下一个成员
+                    std::io::copy(reader, &mut std::io::sink())?;
+                    return Ok(true);
+                };
+
+                if entry.size() > limits.max_entry_size || usage.total.saturating_add(entry.size()) > limits.max_total_size {
+                    failure = Some(format!("压缩包成员 {} 超过大小限制，或压缩比异常，可能是压缩炸弹", entry.name()));
+                    return Ok(false);
+                }
+
+                match write_member(reader, dest, relative, limits, &mut usage) {
+                    Ok(member) => {
+                        members.push(member);
+                        Ok(true)
+                    }
+                    Err(e) => {
+                        failure = Some(e);
+                        Ok(false)
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(members),
+        }
+    }
+}
+
+/// 将成员内容写入解压目录，写入时统计实际解压大小
+fn write_member(
+    reader: &mut dyn Read,
+    dest: &Path,
+    relative: PathBuf,
+    limits: &ArchiveLimits,
+    usage: &mut Usage,
+) -> Result<ArchiveMember, String> {
+    usage.entries += 1;
+    if usage.entries > limits.max_entries {
+        return Err(format!("压缩包文件数超过 {} 个", limits.max_entries));
+    }
+
+    let member_path = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    // 不同成员规范化后可能得到相同的路径（如 `a?.xlsx` 与 `a_.xlsx`），重名时追加序号
+    let mut target = dest.join(&relative);
+    let mut index = 1;
+    while !usage.targets.insert(target.to_string_lossy().to_lowercase()) {
+        index += 1;
+        target = dest.join(numbered_path(&relative, index));
+    }
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let mut out = File::create(&target).map_err(|e| e.to_string())?;
+    let mut remaining = limits.max_entry_size.min(limits.max_total_size - usage.total);
+    let mut buf = vec![0u8; 64 * 1024];
+
+    loop {
+        let n = reader.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        if n as u64 > remaining {
+            drop(out);
+            let _ = std::fs::remove_file(&target);
+            return Err(format!("压缩包成员 {} 解压后超过大小限制", member_path));
+        }
+        remaining -= n as u64;
+        usage.total += n as u64;
+        out.write_all(&buf[..n]).map_err(|e| e.to_string())?;
+    }
+
+    Ok(ArchiveMember { member_path, target })
+}
+
+/// 在文件名后追加序号：`dir/a.xlsx` → `dir/a (2).xlsx`
+fn numbered_path(relative: &Path, index: usize) -> PathBuf {
+    let stem = relative.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let name = match relative.extension() {
+        Some(ext) => format!("{} ({}).{}", stem, index, ext.to_string_lossy()),
+        None => format!("{} ({})", stem, index),
+    };
+    relative.with_file_name(name)
+}
+
+/// 规范化压缩包成员路径
+///
+/// 拒绝绝对路径、盘符和 `..`，去掉保留字符；
+/// macOS 打包时附带的 `__MACOSX/` 和 `.DS_Store` 返回 None
+fn safe_member_path(name: &str) -> Option<PathBuf> {
+    let name = name.replace('\\', "/");
+    let mut path = PathBuf::new();
+
+    for part in name.split('/') {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            "__MACOSX" | ".DS_Store" => return None,
+            _ => {}
+        }
+        // 盘符（C:）或其他带冒号的路径
+        if part.contains(':') {
+            return None;
+        }
+
+        let cleaned: String = part
+            .chars()
+            .map(|c| match c {
+                '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect();
+        let cleaned = cleaned.trim().trim_end_matches('.');
+        if cleaned.is_empty() {
+            continue;
+        }
+        path.push(cleaned);
+    }
+
+    let is_normal = path.components().all(|c| matches!(c, Component::Normal(_)));
+    (is_normal && path.components().next().is_some()).then_some(path)
+}
+
+/// ZIP 成员是否为符号链接
+fn is_symlink(mode: Option<u32>) -> bool {
+    mode.is_some_and(|m| m & 0o170000 == 0o120000)
+}
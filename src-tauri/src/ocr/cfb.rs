@@ -0,0 +1,251 @@
+// 复合文档模块 - 只读解析 OLE 复合文档（Compound File Binary）
+// 用于读取 Outlook .msg 邮件等以复合文档存储的文件
+
+/// 复合文档文件头标识
+pub const CFB_SIGNATURE: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// 扇区链结束标记
+const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+
+/// 无效扇区/目录项编号
+const NO_STREAM: u32 = 0xFFFF_FFFF;
+
+/// 文件头中直接列出的 FAT 扇区数
+const HEADER_DIFAT_ENTRIES: usize = 109;
+
+/// 目录项
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    pub name: String,
+    pub is_storage: bool,   // 存储（目录）或根存储
+    pub is_stream: bool,    // 流（文件）
+    left: u32,
+    right: u32,
+    child: u32,
+    start: u32,
+    size: u64,
+}
+
+/// 复合文档
+pub struct CompoundFile {
+    data: Vec<u8>,
+    sector_size: usize,
+    mini_sector_size: usize,
+    mini_cutoff: u64,
+    fat: Vec<u32>,
+    mini_fat: Vec<u32>,
+    mini_stream: Vec<u8>,
+    pub entries: Vec<DirEntry>,
+}
+
+impl CompoundFile {
+    /// 解析复合文档
+    pub fn parse(data: Vec<u8>) -> Result<Self, String> {
+        if data.len() < 512 || !data.starts_with(CFB_SIGNATURE) {
+            return Err("不是有效的 OLE 复合文档".to_string());
+        }
+
+        let u16_at = |pos: usize| u16::from_le_bytes([data[pos], data[pos + 1]]);
+        let u32_at = |pos: usize| u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]);
+
+        let sector_shift = u16_at(0x1E) as u32;
+        let mini_shift = u16_at(0x20) as u32;
+        if !(7..=16).contains(&sector_shift) || mini_shift >= sector_shift {
+            return Err("复合文档扇区大小无效".to_string());
+        }
+
+        let mut file = CompoundFile {
+            sector_size: 1 << sector_shift,
+            mini_sector_size: 1 << mini_shift,
+            mini_cutoff: u32_at(0x38) as u64,
+            fat: Vec::new(),
+            mini_fat: Vec::new(),
+            mini_stream: Vec::new(),
+            entries: Vec::new(),
+            data: Vec::new(),
+        };
+
+        let first_dir = u32_at(0x30);
+        let first_mini_fat = u32_at(0x3C);
+        let mut difat_sector = u32_at(0x44);
+        let num_difat = u32_at(0x48) as usize;
+
+        // 收集 FAT 扇区编号：文件头中的 109 个 + DIFAT 扇区链
+        let mut fat_sectors: Vec<u32> = (0..HEADER_DIFAT_ENTRIES)
+            .map(|i| u32_at(0x4C + i * 4))
+            .filter(|s| *s < END_OF_CHAIN)
+            .collect();
+
+        file.data = data;
+        let per_sector = file.sector_size / 4;
+
+        // 扇区链可能成环或声明的数量远超实际，按文件中的扇区数限制
+        let sector_count = file.data.len() / file.sector_size;
+        let mut visited = vec![false; sector_count];
+
+        for _ in 0..num_difat.min(sector_count) {
+            if difat_sector >= END_OF_CHAIN {
+                break;
+            }
+            let sector = file.sector(difat_sector)?;
+            if std::mem::replace(&mut visited[difat_sector as usize], true) {
+                return Err("复合文档 DIFAT 扇区链成环".to_string());
+            }
+            let ids: Vec<u32> = sector.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+            fat_sectors.extend(ids[..per_sector - 1].iter().filter(|s| **s < END_OF_CHAIN));
+            difat_sector = ids[per_sector - 1];
+        }
+        fat_sectors.truncate(sector_count);
+
+        let mut fat = Vec::with_capacity(fat_sectors.len() * per_sector);
+        for sector in fat_sectors {
+            let bytes = file.sector(sector)?;
+            fat.extend(bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])));
+        }
+        file.fat = fat;
+
+        // 目录
+        let dir = file.read_chain(first_dir, None)?;
+        file.entries = dir.chunks_exact(128).map(parse_dir_entry).collect();
+        // 512 字节扇区（版本 3）的文件大小高 32 位可能是未清零的垃圾数据
+        if file.sector_size == 512 {
+            for entry in &mut file.entries {
+                entry.size &= 0xFFFF_FFFF;
+            }
+        }
+        let Some(root) = file.entries.first().cloned() else {
+            return Err("复合文档缺少根目录".to_string());
+        };
+
+        // 小扇区分配表和小流数据（存放在根目录项的扇区链中）
+        if first_mini_fat < END_OF_CHAIN {
+            let bytes = file.read_chain(first_mini_fat, None)?;
+            file.mini_fat = bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+        }
+        if root.start < END_OF_CHAIN {
+            file.mini_stream = file.read_chain(root.start, Some(root.size))?;
+        }
+
+        Ok(file)
+    }
+
+    /// 列出存储下的直接子项（目录项编号）
+    pub fn children(&self, storage: usize) -> Vec<usize> {
+        let mut result = Vec::new();
+        let mut stack = vec![self.entries.get(storage).map_or(NO_STREAM, |e| e.child)];
+        let mut visited = vec![false; self.entries.len()];
+
+        // 子项以红黑树组织，遍历左右兄弟即可得到全部子项
+        while let Some(id) = stack.pop() {
+            let index = id as usize;
+            if id == NO_STREAM || index >= self.entries.len() || visited[index] {
+                continue;
+            }
+            visited[index] = true;
+            result.push(index);
+            stack.push(self.entries[index].left);
+            stack.push(self.entries[index].right);
+        }
+
+        result.sort_by(|a, b| self.entries[*a].name.cmp(&self.entries[*b].name));
+        result
+    }
+
+    /// 按名称查找存储下的子项（名称不区分大小写）
+    pub fn find(&self, storage: usize, name: &str) -> Option<usize> {
+        self.children(storage)
+            .into_iter()
+            .find(|i| self.entries[*i].name.eq_ignore_ascii_case(name))
+    }
+
+    /// 读取流的内容
+    pub fn read_stream(&self, entry: usize) -> Result<Vec<u8>, String> {
+        let entry = self.entries.get(entry).ok_or("目录项不存在")?;
+        if !entry.is_stream {
+            return Err(format!("{} 不是流", entry.name));
+        }
+
+        if entry.size < self.mini_cutoff {
+            self.read_mini_chain(entry.start, entry.size)
+        } else {
+            self.read_chain(entry.start, Some(entry.size))
+        }
+    }
+
+    /// 获取扇区数据
+    fn sector(&self, id: u32) -> Result<&[u8], String> {
+        let start = (id as usize + 1) * self.sector_size;
+        self.data
+            .get(start..start + self.sector_size)
+            .ok_or_else(|| format!("复合文档扇区 {} 超出文件范围", id))
+    }
+
+    /// 按 FAT 读取扇区链
+    fn read_chain(&self, start: u32, size: Option<u64>) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        let mut id = start;
+        let mut steps = 0;
+
+        while id < END_OF_CHAIN {
+            // 防止损坏文件中的循环链
+            steps += 1;
+            if steps > self.fat.len() + 1 {
+                return Err("复合文档扇区链存在循环".to_string());
+            }
+            out.extend_from_slice(self.sector(id)?);
+            id = *self.fat.get(id as usize).ok_or("复合文档扇区链损坏")?;
+        }
+
+        if let Some(size) = size {
+            out.truncate(size as usize);
+        }
+        Ok(out)
+    }
+
+    /// 按小扇区分配表读取小流
+    fn read_mini_chain(&self, start: u32, size: u64) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        let mut id = start;
+        let mut steps = 0;
+
+        while id < END_OF_CHAIN {
+            steps += 1;
+            if steps > self.mini_fat.len() + 1 {
+                return Err("复合文档小扇区链存在循环".to_string());
+            }
+            let begin = id as usize * self.mini_sector_size;
+            let chunk = self
+                .mini_stream
+                .get(begin..begin + self.mini_sector_size)
+                .ok_or("复合文档小扇区超出范围")?;
+            out.extend_from_slice(chunk);
+            id = *self.mini_fat.get(id as usize).ok_or("复合文档小扇区链损坏")?;
+        }
+
+        out.truncate(size as usize);
+        Ok(out)
+    }
+}
+
+/// 解析 128 字节的目录项
+fn parse_dir_entry(b: &[u8]) -> DirEntry {
+    let u32_at = |pos: usize| u32::from_le_bytes([b[pos], b[pos + 1], b[pos + 2], b[pos + 3]]);
+
+    let name_len = (u16::from_le_bytes([b[0x40], b[0x41]]) as usize).min(64);
+    let units: Vec<u16> = b[..name_len.saturating_sub(2)]
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    let entry_type = b[0x42];
+
+    DirEntry {
+        name: String::from_utf16_lossy(&units),
+        is_storage: entry_type == 1 || entry_type == 5,
+        is_stream: entry_type == 2,
+        left: u32_at(0x44),
+        right: u32_at(0x48),
+        child: u32_at(0x4C),
+        start: u32_at(0x74),
+        size: u32_at(0x78) as u64 | (u32_at(0x7C) as u64) << 32,
+    }
+}
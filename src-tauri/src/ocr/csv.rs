@@ -0,0 +1,200 @@
+// CSV 解析模块 - 读取 CSV/TSV 等分隔文本文件
+// 自动识别文本编码（UTF-8/UTF-16/GBK/GB18030）、分隔符和引号字符
+
+use crate::ocr::{encoding, ExtractedTable};
+
+/// CSV 处理器
+pub struct CsvProcessor;
+
+/// 候选分隔符（按优先级排列）
+const DELIMITERS: &[char] = &[',', '\t', ';', '|'];
+
+/// 候选引号字符
+const QUOTES: &[char] = &['"', '\''];
+
+/// 识别分隔符和引号时采样的行数
+const SAMPLE_LINES: usize = 50;
+
+/// 分隔文本的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub delimiter: char,      // 字段分隔符
+    pub quote: Option<char>,  // 引号字符，None 表示字段不使用引号
+}
+
+impl CsvProcessor {
+    /// 从 CSV/TSV 文件提取表格
+    ///
+    /// # 参数
+    /// * `file_path` - 文件路径
+    ///
+    /// # 返回
+    /// 提取的表格，全空的行会被跳过
+    pub fn extract_table(file_path: &str) -> Result<ExtractedTable, String> {
+        let bytes = std::fs::read(file_path).map_err(|e| e.to_string())?;
+        let (text, _) = encoding::decode_text(&bytes);
+
+        // .tsv 文件优先按制表符分隔
+        let is_tsv = file_path.to_ascii_lowercase().ends_with(".tsv");
+        let dialect = Self::detect_dialect(&text, is_tsv);
+
+        let rows = Self::parse(&text, dialect)
+            .into_iter()
+            .filter(|row| row.iter().any(|cell| !cell.trim().is_empty()))
+            .collect();
+
+        Ok(ExtractedTable { rows, ..Default::default() })
+    }
+
+    /// 识别分隔符和引号字符
+    ///
+    /// 对每个候选组合解析前若干行，选择每行字段数一致且大于 1 的行数最多、
+    /// 字段数最多的组合；都不满足时退回逗号（TSV 文件为制表符）
+    pub fn detect_dialect(text: &str, prefer_tab: bool) -> CsvDialect {
+        let sample = Self::sample(text);
+        let fallback = CsvDialect {
+            delimiter: if prefer_tab { '\t' } else { ',' },
+            quote: Some('"'),
+        };
+
+        let quote = Self::detect_quote(&sample);
+        let mut best: Option<((usize, usize), CsvDialect)> = None;
+
+        for &delimiter in DELIMITERS {
+            let dialect = CsvDialect { delimiter, quote };
+            let rows = Self::parse(&sample, dialect);
+            let rows: Vec<&Vec<String>> = rows
+                .iter()
+                .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+                .collect();
+
+            let Some(first) = rows.first() else { continue };
+            let width = first.len();
+            if width < 2 {
+                continue;
+            }
+
+            let consistent = rows.iter().filter(|row| row.len() == width).count();
+            let mut score = (consistent, width);
+            if prefer_tab && delimiter == '\t' {
+                score.0 += rows.len();
+            }
+
+            if best.is_none_or(|(best_score, _)| score > best_score) {
+                best = Some((score, dialect));
+            }
+        }
+
+        best.map(|(_, dialect)| dialect).unwrap_or(fallback)
+    }
+
+    /// 识别引号字符：统计紧贴字段边界的引号出现次数
+    fn detect_quote(sample: &str) -> Option<char> {
+        let is_boundary = |c: Option<char>| match c {
+            None => true,
+            Some(c) => DELIMITERS.contains(&c) || c == '\n' || c == '\r' || c == ' ',
+        };
+
+        let chars: Vec<char> = sample.chars().collect();
+        let mut best = None;
+        let mut best_count = 0;
+
+        for &quote in QUOTES {
+            let count = chars
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c == quote)
+                .filter(|(i, _)| {
+                    let before = if *i == 0 { None } else { Some(chars[i - 1]) };
+                    let after = chars.get(i + 1).copied();
+                    is_boundary(before) || is_boundary(after)
+                })
+                .count();
+
+            if count > best_count {
+                best = Some(quote);
+                best_count = count;
+            }
+        }
+
+        // 单引号常见于正文（如英文缩写），只有明显成对出现时才视为引号
+        match best {
+            Some('\'') if best_count < 4 => Some('"'),
+            None => Some('"'),
+            quote => quote,
+        }
+    }
+
+    /// 取前若干行作为识别样本（引号内的换行不计入行数）
+    fn sample(text: &str) -> String {
+        let mut lines = 0;
+        let mut in_quotes = false;
+
+        for (i, c) in text.char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                '\n' if !in_quotes => {
+                    lines += 1;
+                    if lines >= SAMPLE_LINES {
+                        return text[..i].to_string();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        text.to_string()
+    }
+
+    /// 按指定格式解析分隔文本
+    ///
+    /// 引号内可包含分隔符和换行，连续两个引号表示一个引号字符；
+    /// 行尾支持 `\n` 和 `\r\n`
+    pub fn parse(text: &str, dialect: CsvDialect) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if in_quotes {
+                if Some(c) == dialect.quote {
+                    if chars.peek() == Some(&c) {
+                        field.push(c);
+                        chars.next();
+                    } else {
+                        in_quotes = false;
+                    }
+                } else {
+                    field.push(c);
+                }
+                continue;
+            }
+
+            match c {
+                c if c == dialect.delimiter => {
+                    row.push(std::mem::take(&mut field));
+                }
+                // 只有字段开头的引号才开始引用，字段中间的引号按普通字符处理
+                c if Some(c) == dialect.quote && field.trim().is_empty() => {
+                    field.clear();
+                    in_quotes = true;
+                }
+                '\r' if chars.peek() == Some(&'\n') => {}
+                '\n' | '\r' => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                c => field.push(c),
+            }
+        }
+
+        if !field.is_empty() || !row.is_empty() {
+            row.push(field);
+            rows.push(row);
+        }
+
+        rows
+    }
+}
@@ -0,0 +1,214 @@
+// 文件类型识别模块 - 根据文件内容（魔数和容器结构）判断实际格式
+// 不依赖扩展名，改名后的文件（如另存为 .xlsx 的 .xls、扩展名为 .pdf 的 JPEG）也能选择正确的解析器
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use crate::ocr::cfb::{CompoundFile, CFB_SIGNATURE};
+use crate::ocr::csv::CsvProcessor;
+use crate::ocr::{encoding, mime};
+
+/// 文本类格式识别时读取的文件头长度
+const SNIFF_BYTES: u64 = 64 * 1024;
+
+/// 根据文件内容识别文件类型
+///
+/// # 参数
+/// * `file_path` - 文件路径
+///
+/// # 返回
+/// 文件类型：pdf、image、word、excel、pptx、odt、rtf、html、email、csv、tsv、archive
+pub fn detect_file_type(file_path: &str) -> Result<&'static str, String> {
+    let file = File::open(file_path).map_err(|e| e.to_string())?;
+    let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut head = Vec::new();
+    file.take(SNIFF_BYTES)
+        .read_to_end(&mut head)
+        .map_err(|e| e.to_string())?;
+
+    if head.is_empty() {
+        return Err("文件为空".to_string());
+    }
+
+    if let Some(file_type) = detect_binary(&head, file_len) {
+        return Ok(file_type);
+    }
+
+    if head.starts_with(CFB_SIGNATURE) {
+        return detect_compound(file_path);
+    }
+
+    if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+        return detect_zip(file_path);
+    }
+
+    // 截断到最后一个完整行，避免文件头截断在多字节字符中间影响编码识别
+    if head.len() as u64 == SNIFF_BYTES {
+        if let Some(pos) = head.iter().rposition(|b| *b == b'\n') {
+            // UTF-16LE 的换行是 0A 00
+            let end = if head.get(pos + 1) == Some(&0) { pos + 2 } else { pos + 1 };
+            head.truncate(end);
+        }
+    }
+
+    detect_text(&head, file_path)
+}
+
+/// 识别魔数固定的二进制格式
+fn detect_binary(head: &[u8], file_len: u64) -> Option<&'static str> {
+    let is_image = head.starts_with(b"\x89PNG\r\n\x1a\n")
+        || head.starts_with(&[0xFF, 0xD8, 0xFF])
+        || head.starts_with(b"II*\0")
+        || head.starts_with(b"MM\0*")
+        || head.starts_with(b"GIF87a")
+        || head.starts_with(b"GIF89a")
+        || is_bmp(head, file_len)
+        || (head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"WEBP");
+
+    if is_image {
+        Some("image")
+    } else if head.starts_with(b"%PDF-") {
+        Some("pdf")
+    } else if head.starts_with(&[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C]) {
+        Some("archive")
+    } else {
+        None
+    }
+}
+
+/// 判断是否为 BMP 位图
+///
+/// "BM" 魔数只有两个字节，以 BM 开头的文本很常见，因此还要求头部记录的文件大小
+/// 不超过实际长度，且 DIB 信息头长度是已知版本之一
+fn is_bmp(head: &[u8], file_len: u64) -> bool {
+    if head.len() < 18 || !head.starts_with(b"BM") {
+        return false;
+    }
+    let declared_len = u32::from_le_bytes([head[2], head[3], head[4], head[5]]) as u64;
+    let dib_header_len = u32::from_le_bytes([head[14], head[15], head[16], head[17]]);
+
+    declared_len <= file_len && matches!(dib_header_len, 12 | 40 | 52 | 56 | 108 | 124)
+}
+
+/// 识别 OLE 复合文档：旧版 Excel、Outlook 邮件或加密的 Office 文档
+fn detect_compound(file_path: &str) -> Result<&'static str, String> {
+    let bytes = std::fs::read(file_path).map_err(|e| e.to_string())?;
+    let file = CompoundFile::parse(bytes)?;
+    let names: Vec<String> = file
+        .children(0)
+        .into_iter()
+        .map(|i| file.entries[i].name.to_ascii_lowercase())
+        .collect();
+    let has = |name: &str| names.iter().any(|n| n == name);
+
+    if has("workbook") || has("book") {
+        Ok("excel")
+    } else if has("__properties_version1.0") || names.iter().any(|n| n.starts_with("__substg1.0_")) {
+        Ok("email")
+    } else if has("encryptedpackage") {
+        Err("文件是加密的 Office 文档，请先取消密码保护".to_string())
+    } else if has("worddocument") {
+        Err("不支持旧版 Word (.doc) 文件，请另存为 .docx".to_string())
+    } else if has("powerpoint document") {
+        Err("不支持旧版 PowerPoint (.ppt) 文件，请另存为 .pptx".to_string())
+    } else {
+        Err("无法识别的 OLE 复合文档".to_string())
+    }
+}
+
+/// 识别 ZIP 容器：OOXML、OpenDocument 或普通压缩包
+fn detect_zip(file_path: &str) -> Result<&'static str, String> {
+    let file = File::open(file_path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+
+    // OpenDocument 的第一个成员是未压缩的 mimetype
+    if let Ok(mut entry) = archive.by_name("mimetype") {
+        let mut mimetype = String::new();
+        entry.read_to_string(&mut mimetype).map_err(|e| e.to_string())?;
+        return match mimetype.trim() {
+            "application/vnd.oasis.opendocument.text" => Ok("odt"),
+            "application/vnd.oasis.opendocument.spreadsheet" => Ok("excel"),
+            other => Err(format!("不支持的 OpenDocument 类型: {}", other)),
+        };
+    }
+
+    // 部件名不区分大小写
+    let names: Vec<String> = archive.file_names().map(|n| n.to_ascii_lowercase()).collect();
+    let has = |name: &str| names.iter().any(|n| n == name);
+
+    if has("word/document.xml") {
+        Ok("word")
+    } else if has("xl/workbook.xml") || has("xl/workbook.bin") {
+        Ok("excel")
+    } else if has("ppt/presentation.xml") {
+        Ok("pptx")
+    } else if has("[content_types].xml") {
+        Err("不支持的 Office Open XML 文档类型".to_string())
+    } else {
+        Ok("archive")
+    }
+}
+
+/// 识别文本格式：RTF、MIME（邮件或 MHTML）、HTML、CSV/TSV
+fn detect_text(head: &[u8], file_path: &str) -> Result<&'static str, String> {
+    let (text, _) = encoding::decode_text(head);
+    let trimmed = text.trim_start_matches('\u{feff}').trim_start();
+
+    if trimmed.starts_with("{\\rtf") {
+        return Ok("rtf");
+    }
+
+    // 二进制内容解码后会包含大量控制字符
+    let is_text = !text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\t' | '\r' | '\n' | '\x0c'));
+    if !is_text {
+        return Err("无法识别的文件格式".to_string());
+    }
+
+    if mime::looks_like_mime(trimmed.as_bytes()) {
+        // 浏览器保存的网页存档是 multipart/related 或单个 HTML 段
+        let content_type = mime::MimePart::parse(trimmed.as_bytes()).mime_type();
+        return match content_type.as_str() {
+            "multipart/related" | "text/html" => Ok("html"),
+            _ => Ok("email"),
+        };
+    }
+
+    // 只有以标记开头的文本才视为 HTML，正文中提到 <table> 的 CSV 等不受影响
+    let lower: String = skip_prolog(trimmed).chars().take(16).collect::<String>().to_lowercase();
+    if ["<!doctype", "<html", "<table"].iter().any(|tag| lower.starts_with(tag)) {
+        return Ok("html");
+    }
+
+    // 多数行能按同一分隔符拆成多列时视为 CSV/TSV
+    let prefer_tab = file_path.to_ascii_lowercase().ends_with(".tsv");
+    let dialect = CsvProcessor::detect_dialect(&text, prefer_tab);
+    let rows = CsvProcessor::parse(&text, dialect);
+    let sample: Vec<_> = rows.iter().take(20).filter(|r| r.iter().any(|c| !c.trim().is_empty())).collect();
+    let multi_column = sample.iter().filter(|r| r.len() > 1).count();
+
+    if !sample.is_empty() && multi_column * 2 > sample.len() {
+        return Ok(if dialect.delimiter == '\t' { "tsv" } else { "csv" });
+    }
+
+    Err("无法识别的文件格式：文本内容不是表格".to_string())
+}
+
+/// 跳过文本开头的 XML 声明和注释（如 `<?xml ...?>`、`<!-- saved from url -->`）
+fn skip_prolog(mut text: &str) -> &str {
+    loop {
+        let end = if text.starts_with("<!--") {
+            text.find("-->").map(|i| i + 3)
+        } else if text.starts_with("<?") {
+            text.find("?>").map(|i| i + 2)
+        } else {
+            return text;
+        };
+
+        match end {
+            Some(end) => text = text[end..].trim_start(),
+            // 未闭合的注释或声明，保持原样
+            None => return text,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+// 邮件解析模块 - 读取 .eml（MIME）和 Outlook .msg 邮件
+// 提取 HTML 正文中的表格和所有附件，附件交由对应的解析器处理
+
+use crate::ocr::cfb::{CompoundFile, CFB_SIGNATURE};
+use crate::ocr::html::HtmlProcessor;
+use crate::ocr::mime::{self, MimePart};
+use crate::ocr::{encoding, ExtractedTable};
+
+/// 邮件处理器
+pub struct EmailProcessor;
+
+/// 解析后的邮件
+#[derive(Debug, Default)]
+pub struct EmailMessage {
+    pub subject: String,                   // 邮件主题
+    pub text_body: String,                 // 纯文本正文
+    pub html_bodies: Vec<String>,          // HTML 正文（可能有多段）
+    pub attachments: Vec<EmailAttachment>, // 附件
+}
+
+/// 邮件附件
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub file_name: String,             // 附件文件名
+    pub content_type: Option<String>,  // 声明的内容类型
+    pub data: Vec<u8>,                 // 附件内容
+}
+
+impl EmailProcessor {
+    /// 解析邮件文件，按文件头区分 .msg（OLE 复合文档）和 .eml
+    pub fn parse_file(file_path: &str) -> Result<EmailMessage, String> {
+        let bytes = std::fs::read(file_path).map_err(|e| e.to_string())?;
+
+        if bytes.starts_with(CFB_SIGNATURE) {
+            Self::parse_msg(bytes)
+        } else {
+            Ok(Self::parse_eml(&bytes))
+        }
+    }
+
+    /// 提取邮件 HTML 正文中的表格
+    pub fn extract_tables(message: &EmailMessage) -> Vec<ExtractedTable> {
+        message
+            .html_bodies
+            .iter()
+            .flat_map(|html| HtmlProcessor::parse_tables(html))
+            .collect()
+    }
+
+    /// 解析 .eml 邮件
+    pub fn parse_eml(bytes: &[u8]) -> EmailMessage {
+        let root = MimePart::parse(bytes);
+        let mut message = EmailMessage {
+            subject: root.header("subject").map(mime::decode_header).unwrap_or_default(),
+            ..Default::default()
+        };
+
+        for part in root.leaves() {
+            let mime_type = part.mime_type();
+
+            // 转发的邮件作为 .eml 附件处理
+            if mime_type == "message/rfc822" {
+                let name = part.filename().unwrap_or_else(|| {
+                    let inner = MimePart::parse(&part.body);
+                    let subject = inner.header("subject").map(mime::decode_header).unwrap_or_default();
+                    format!("{}.eml", if subject.trim().is_empty() { "message" } else { subject.trim() })
+                });
+                message.attachments.push(EmailAttachment {
+                    file_name: name,
+                    content_type: Some(mime_type),
+                    data: part.body.clone(),
+                });
+                continue;
+            }
+
+            // 正文中引用的内嵌图片（签名、图标等）不作为附件
+            let is_inline_image = mime_type.starts_with("image/")
+                && part.header("content-id").is_some()
+                && !part
+                    .header("content-disposition")
+                    .is_some_and(|v| v.trim().to_ascii_lowercase().starts_with("attachment"));
+
+            if part.is_attachment() && !is_inline_image {
+                let index = message.attachments.len() + 1;
+                message.attachments.push(EmailAttachment {
+                    file_name: part.filename().unwrap_or_else(|| format!("attachment-{}", index)),
+                    content_type: Some(mime_type),
+                    data: part.body.clone(),
+                });
+            } else if mime_type == "text/html" {
+                message.html_bodies.push(part.text());
+            } else if mime_type == "text/plain" && message.text_body.is_empty() {
+                message.text_body = part.text();
+            }
+        }
+
+        message
+    }
+
+    /// 解析 Outlook .msg 邮件
+    ///
+    /// 属性以 `__substg1.0_<属性ID><类型>` 流存储：001F 为 UTF-16 字符串，
+    /// 001E 为 8 位字符串，0102 为二进制；附件位于 `__attach_version1.0_#NNNNNNNN` 存储中
+    pub fn parse_msg(bytes: Vec<u8>) -> Result<EmailMessage, String> {
+        let file = CompoundFile::parse(bytes)?;
+        let root = 0;
+
+        let mut message = EmailMessage {
+            subject: msg_string(&file, root, "0037").unwrap_or_default(),
+            text_body: msg_string(&file, root, "1000").unwrap_or_default(),
+            ..Default::default()
+        };
+
+        // HTML 正文通常以二进制存储，字符集由 <meta> 声明
+        if let Some(html) = msg_binary(&file, root, "1013") {
+            message.html_bodies.push(HtmlProcessor::decode_html(&html));
+        } else if let Some(html) = msg_string(&file, root, "1013") {
+            message.html_bodies.push(html);
+        }
+
+        for child in file.children(root) {
+            let entry = &file.entries[child];
+            if !entry.is_storage || !entry.name.starts_with("__attach_version1.0_") {
+                continue;
+            }
+
+            // 嵌入的 Outlook 邮件（对象类型 000D）没有二进制数据流，跳过
+            let Some(data) = msg_binary(&file, child, "3701") else { continue };
+
+            let index = message.attachments.len() + 1;
+            let file_name = msg_string(&file, child, "3707")
+                .or_else(|| msg_string(&file, child, "3704"))
+                .or_else(|| msg_string(&file, child, "3001"))
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_else(|| format!("attachment-{}", index));
+
+            message.attachments.push(EmailAttachment {
+                file_name,
+                content_type: msg_string(&file, child, "370E"),
+                data,
+            });
+        }
+
+        Ok(message)
+    }
+}
+
+/// 读取 .msg 字符串属性（优先 UTF-16，其次 8 位字符串）
+fn msg_string(file: &CompoundFile, storage: usize, id: &str) -> Option<String> {
+    if let Some(entry) = file.find(storage, &format!("__substg1.0_{}001F", id)) {
+        let bytes = file.read_stream(entry).ok()?;
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        return Some(String::from_utf16_lossy(&units).trim_end_matches('\0').to_string());
+    }
+
+    let entry = file.find(storage, &format!("__substg1.0_{}001E", id))?;
+    let bytes = file.read_stream(entry).ok()?;
+    Some(encoding::decode_text(&bytes).0.trim_end_matches('\0').to_string())
+}
+
+/// 读取 .msg 二进制属性
+fn msg_binary(file: &CompoundFile, storage: usize, id: &str) -> Option<Vec<u8>> {
+    let entry = file.find(storage, &format!("__substg1.0_{}0102", id))?;
+    file.read_stream(entry).ok()
+}
@@ -0,0 +1,68 @@
+// 文本编码模块 - 识别并解码外部文本文件
+// CSV、HTML、邮件等文本来源常见 UTF-8、UTF-16 和 GBK/GB18030 编码
+
+use encoding_rs::{Encoding, GB18030, UTF_16BE, UTF_16LE, UTF_8};
+
+/// 识别编码并解码文本
+///
+/// 依次检查 BOM、无 BOM 的 UTF-16（按零字节分布判断）、UTF-8 合法性，
+/// 都不满足时按 GB18030（兼容 GBK/GB2312）解码
+///
+/// # 返回
+/// 解码后的文本和识别出的编码名称
+pub fn decode_text(bytes: &[u8]) -> (String, &'static str) {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return (text.into_owned(), encoding.name());
+    }
+
+    if let Some(encoding) = sniff_utf16(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(bytes);
+        return (text.into_owned(), encoding.name());
+    }
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return (text.to_string(), UTF_8.name());
+    }
+
+    let (text, _) = GB18030.decode_without_bom_handling(bytes);
+    (text.into_owned(), GB18030.name())
+}
+
+/// 按声明的字符集名称解码文本
+/// BOM 优先于声明；名称无法识别时退回自动识别
+pub fn decode_with_label(bytes: &[u8], label: &str) -> String {
+    if Encoding::for_bom(bytes).is_some() {
+        return decode_text(bytes).0;
+    }
+
+    match Encoding::for_label(label.trim().as_bytes()) {
+        // GBK/GB2312 声明的文件实际常含 GB18030 扩展字符，统一按超集解码
+        Some(encoding) if encoding == encoding_rs::GBK => {
+            GB18030.decode_without_bom_handling(bytes).0.into_owned()
+        }
+        Some(encoding) => encoding.decode_without_bom_handling(bytes).0.into_owned(),
+        None => decode_text(bytes).0,
+    }
+}
+
+/// 识别无 BOM 的 UTF-16 文本
+/// ASCII 字符为主的 UTF-16 文本中，高字节位置几乎都是零
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+
+    let pairs = sample.len() / 2;
+    let even_zeros = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_zeros = sample.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+
+    if odd_zeros * 10 >= pairs * 3 && even_zeros * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even_zeros * 10 >= pairs * 3 && odd_zeros * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
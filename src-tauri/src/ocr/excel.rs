@@ -1,15 +1,87 @@
 // Excel 解析模块 - 使用 calamine 读取 Excel 文件
-// 支持 .xlsx 和 .xls 格式，提取所有工作表数据
+// 支持 .xlsx、.xlsm、.xlsb、.xls 和 .ods 格式，按文件内容识别格式，提取所有工作表数据
 
-use calamine::{Reader, Xlsx, DataType, Range};
-use crate::ocr::ExtractedTable;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use calamine::{
+    open_workbook_from_rs, Data, Dimensions, Ods, Range, Reader, SheetType, SheetVisible, Sheets,
+    Xls, Xlsb, Xlsx,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::ocr::{CellValue, ExtractedTable, FormulaCell, MergedRange, TableSource};
+use crate::ocr::excel_format::{self, NumberFormats};
+use crate::ocr::excel_formula::{EvaluatedCells, FormulaBook};
 
 /// Excel 处理器
 pub struct ExcelProcessor;
 
+/// 工作簿格式（由文件内容判断，与扩展名无关）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkbookFormat {
+    Xlsx,   // Office Open XML 工作簿（含 .xlsm）
+    Xlsb,   // Excel 二进制工作簿
+    Xls,    // Excel 97-2003（OLE 复合文档）
+    Ods,    // OpenDocument 电子表格（WPS / LibreOffice）
+}
+
+/// 合并单元格处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeMode {
+    #[default]
+    Fill,     // 将左上角的值向下/向右填充到整个合并区域，并记录区域信息
+    Span,     // 只记录区域信息，合并区域内其余单元格保持为空
+    Ignore,   // 忽略合并区域
+}
+
+/// 工作表筛选条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum SheetFilter {
+    Name(String),    // 按名称精确匹配
+    Index(usize),    // 按工作簿中的位置（从 0 开始，含隐藏工作表）
+    Regex(String),   // 按名称正则匹配
+}
+
+/// Excel 导入选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExcelOptions {
+    pub merge_mode: MergeMode,     // 合并单元格处理方式
+    pub sheets: Vec<SheetFilter>,  // 工作表筛选条件，满足任一条件即导入；为空时导入全部
+    pub include_hidden: bool,      // 是否导入隐藏（含深度隐藏）工作表
+    pub trim_empty: bool,          // 是否裁剪四周的空行空列
+    pub split_blocks: bool,        // 是否将工作表按空行/空列拆分为多个独立表格
+    pub evaluate_formulas: bool,   // 缺少缓存值时是否对公式求值（仅支持安全子集）
+    pub streaming: bool,           // 是否使用流式读取（大文件会自动启用）
+}
+
+impl Default for ExcelOptions {
+    fn default() -> Self {
+        Self {
+            merge_mode: MergeMode::default(),
+            sheets: Vec::new(),
+            include_hidden: false,
+            trim_empty: true,
+            split_blocks: false,
+            evaluate_formulas: false,
+            streaming: false,
+        }
+    }
+}
+
+/// OLE 复合文档文件头（.xls）
+const CFB_MAGIC: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// ZIP 文件头（.xlsx / .xlsb / .ods）
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
 /// Excel 数据容器
 #[derive(Debug)]
 pub struct ExcelData {
+    pub format: WorkbookFormat,  // 工作簿格式
     pub sheets: Vec<SheetData>,  // 所有工作表数据
 }
 
@@ -17,11 +89,33 @@ pub struct ExcelData {
 #[derive(Debug)]
 pub struct SheetData {
     pub name: String,                // 工作表名称
-    pub data: Vec<Vec<String>>,      // 工作表数据（二维数组）
+    pub data: Vec<Vec<String>>,      // 工作表数据（按数字格式渲染的显示文本）
+    pub raw: Vec<Vec<CellValue>>,    // 保留类型的原始值，与 data 一一对应
+    pub merges: Vec<MergedRange>,    // 合并单元格区域（相对 data 左上角）
+    pub origin: (u32, u32),          // data 左上角在工作表中的绝对位置（行, 列，从 0 开始）
+    pub formulas: Vec<FormulaCell>,  // 公式单元格（相对 data 左上角）
+}
+
+/// 编译后的工作表筛选条件
+enum CompiledFilter {
+    Name(String),
+    Index(usize),
+    Regex(Regex),
+}
+
+impl CompiledFilter {
+    /// 判断工作表是否满足筛选条件
+    fn matches(&self, index: usize, name: &str) -> bool {
+        match self {
+            CompiledFilter::Name(n) => n == name,
+            CompiledFilter::Index(i) => *i == index,
+            CompiledFilter::Regex(re) => re.is_match(name),
+        }
+    }
 }
 
 impl ExcelProcessor {
-    /// 从 Excel 提取所有数据
+    /// 从 Excel 提取所有数据（使用默认导入选项）
     ///
     /// # 参数
     /// * `file_path` - Excel 文件路径
@@ -29,57 +123,610 @@ impl ExcelProcessor {
     /// # 返回
     /// 所有工作表的数据
     pub fn extract_all(file_path: &str) -> Result<ExcelData, String> {
-        let workbook: Xlsx<_> = calamine::open_reader(file_path)
-            .map_err(|e| e.to_string())?;
+        Self::extract_with_options(file_path, &ExcelOptions::default())
+    }
+
+    /// 按导入选项从 Excel 提取所有数据
+    ///
+    /// # 参数
+    /// * `file_path` - Excel 文件路径
+    /// * `options` - 导入选项
+    pub fn extract_with_options(file_path: &str, options: &ExcelOptions) -> Result<ExcelData, String> {
+        let format = Self::detect_format(file_path)?;
+        let mut workbook = Self::open_workbook(file_path, format)?;
+
+        // 只有 xlsx 能读取到单元格格式代码，其他格式按值类型渲染
+        let formats = match format {
+            WorkbookFormat::Xlsx => NumberFormats::load_xlsx(file_path)?,
+            _ => NumberFormats::default(),
+        };
+
+        // xlsx 的合并区域需预先加载
+        if options.merge_mode != MergeMode::Ignore {
+            if let Sheets::Xlsx(xlsx) = &mut workbook {
+                xlsx.load_merged_regions().map_err(|e| e.to_string())?;
+            }
+        }
+
+        let selected = Self::select_sheets(&workbook, options)?;
+
+        // 公式可能引用未选中的工作表，求值需使用整个工作簿
+        let mut evaluated = if options.evaluate_formulas {
+            Self::evaluate_formulas(&mut workbook)?
+        } else {
+            HashMap::new()
+        };
 
         let mut sheets = Vec::new();
 
-        for (name, range) in workbook.worksheets() {
-            let data = Self::range_to_vec(&range);
+        for name in selected {
+            let range = workbook.worksheet_range(&name).map_err(|e| e.to_string())?;
+            // 读取公式失败不影响单元格值的导入
+            let formula_range = workbook.worksheet_formula(&name).unwrap_or_default();
+            let (range, mut formulas) =
+                Self::apply_formulas(range, &formula_range, evaluated.remove(&name).unwrap_or_default());
+
+            let mut data = Self::range_to_vec(&range, &name, &formats);
+            let mut raw = Self::range_to_raw(&range);
+            let mut origin = range.start().unwrap_or((0, 0));
+
+            let mut merges = match options.merge_mode {
+                MergeMode::Ignore => Vec::new(),
+                _ => Self::merged_ranges(&mut workbook, &name, &range),
+            };
+
+            if options.merge_mode == MergeMode::Fill {
+                Self::fill_merged(&mut data, &merges);
+                Self::fill_merged(&mut raw, &merges);
+            }
+
+            if options.trim_empty {
+                let (top, left) = Self::trim_empty_borders(&mut data, &mut raw, &mut merges);
+                origin = (origin.0 + top as u32, origin.1 + left as u32);
+
+                let height = data.len();
+                let width = data.iter().map(|row| row.len()).max().unwrap_or(0);
+                formulas.retain_mut(|cell| {
+                    if cell.row < top || cell.col < left {
+                        return false;
+                    }
+                    cell.row -= top;
+                    cell.col -= left;
+                    cell.row < height && cell.col < width
+                });
+            }
+
             sheets.push(SheetData {
                 name,
                 data,
+                raw,
+                merges,
+                origin,
+                formulas,
             });
         }
 
-        Ok(ExcelData { sheets })
+        Ok(ExcelData { format, sheets })
+    }
+
+    /// 对整个工作簿中缺少缓存值的公式求值
+    ///
+    /// # 返回
+    /// 工作表名 → (绝对坐标 → 计算结果)
+    fn evaluate_formulas(workbook: &mut Sheets<BufReader<File>>) -> Result<EvaluatedCells, String> {
+        let names: Vec<String> = workbook
+            .sheets_metadata()
+            .iter()
+            .filter(|sheet| sheet.typ == SheetType::WorkSheet)
+            .map(|sheet| sheet.name.clone())
+            .collect();
+
+        let mut book = FormulaBook::new();
+
+        for name in names {
+            let values = workbook.worksheet_range(&name).map_err(|e| e.to_string())?;
+            let formulas = workbook.worksheet_formula(&name).unwrap_or_default();
+            book.add_sheet(&name, values, &formulas);
+        }
+
+        Ok(book.evaluate_missing())
+    }
+
+    /// 将公式信息并入单元格区域
+    ///
+    /// 区域扩展到覆盖所有公式单元格（缺少缓存值的公式单元格可能不在已使用区域内），
+    /// 并写入求值结果
+    ///
+    /// # 返回
+    /// 扩展后的区域，以及相对区域左上角的公式单元格列表
+    fn apply_formulas(
+        range: Range<Data>,
+        formula_range: &Range<String>,
+        evaluated: HashMap<(u32, u32), Data>,
+    ) -> (Range<Data>, Vec<FormulaCell>) {
+        let (Some(f_start), Some(f_end)) = (formula_range.start(), formula_range.end()) else {
+            return (range, Vec::new());
+        };
+
+        let (start, end) = match (range.start(), range.end()) {
+            (Some(s), Some(e)) => (
+                (s.0.min(f_start.0), s.1.min(f_start.1)),
+                (e.0.max(f_end.0), e.1.max(f_end.1)),
+            ),
+            _ => (f_start, f_end),
+        };
+
+        let mut expanded = if range.start() == Some(start) && range.end() == Some(end) {
+            range
+        } else {
+            let mut expanded = Range::new(start, end);
+            let (s_row, s_col) = range.start().unwrap_or((0, 0));
+            for (r, c, value) in range.used_cells() {
+                expanded.set_value((s_row + r as u32, s_col + c as u32), value.clone());
+            }
+            expanded
+        };
+
+        let mut formulas = Vec::new();
+
+        for (r, c, formula) in formula_range.used_cells() {
+            if formula.is_empty() {
+                continue;
+            }
+
+            let position = (f_start.0 + r as u32, f_start.1 + c as u32);
+            let value = evaluated.get(&position).cloned();
+            let is_evaluated = value.is_some();
+            if let Some(value) = value {
+                expanded.set_value(position, value);
+            }
+
+            formulas.push(FormulaCell {
+                row: (position.0 - start.0) as usize,
+                col: (position.1 - start.1) as usize,
+                formula: format!("={}", formula.trim_start_matches('=')),
+                evaluated: is_evaluated,
+            });
+        }
+
+        (expanded, formulas)
+    }
+
+    /// 读取工作表的合并区域，并换算为相对已使用区域的坐标
+    /// calamine 目前只支持 xlsx 与 xls 的合并区域，xlsb / ods 返回空列表
+    fn merged_ranges(
+        workbook: &mut Sheets<BufReader<File>>,
+        sheet: &str,
+        range: &Range<Data>,
+    ) -> Vec<MergedRange> {
+        let dimensions: Vec<Dimensions> = match workbook {
+            Sheets::Xlsx(xlsx) => xlsx
+                .merged_regions_by_sheet(sheet)
+                .into_iter()
+                .map(|(_, _, dims)| *dims)
+                .collect(),
+            Sheets::Xls(xls) => xls.worksheet_merge_cells(sheet).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        let Some((start_row, start_col)) = range.start() else {
+            return Vec::new();
+        };
+        let (height, width) = range.get_size();
+
+        dimensions
+            .into_iter()
+            .filter_map(|dims| {
+                // 裁剪到已使用区域内，完全落在区域外的合并区域忽略
+                let top = dims.start.0.max(start_row) - start_row;
+                let left = dims.start.1.max(start_col) - start_col;
+                let bottom = (dims.end.0.checked_sub(start_row)? as usize).min(height.checked_sub(1)?);
+                let right = (dims.end.1.checked_sub(start_col)? as usize).min(width.checked_sub(1)?);
+                let (top, left) = (top as usize, left as usize);
+
+                if top > bottom || left > right || (top == bottom && left == right) {
+                    return None;
+                }
+
+                Some(MergedRange {
+                    row: top,
+                    col: left,
+                    row_span: bottom - top + 1,
+                    col_span: right - left + 1,
+                })
+            })
+            .collect()
+    }
+
+    /// 将合并区域左上角的值填充到区域内所有单元格
+    fn fill_merged<T: Clone>(rows: &mut [Vec<T>], merges: &[MergedRange]) {
+        for merge in merges {
+            let Some(value) = rows.get(merge.row).and_then(|r| r.get(merge.col)).cloned() else {
+                continue;
+            };
+
+            for row in rows.iter_mut().skip(merge.row).take(merge.row_span) {
+                for cell in row.iter_mut().skip(merge.col).take(merge.col_span) {
+                    *cell = value.clone();
+                }
+            }
+        }
+    }
+
+    /// 按导入选项列出需要导入的工作表（按工作簿顺序，图表工作表等没有单元格数据的工作表除外）
+    pub fn select_sheets(
+        workbook: &Sheets<BufReader<File>>,
+        options: &ExcelOptions,
+    ) -> Result<Vec<String>, String> {
+        let filters = Self::compile_filters(&options.sheets)?;
+
+        Ok(workbook
+            .sheets_metadata()
+            .iter()
+            .enumerate()
+            .filter(|(_, sheet)| sheet.typ == SheetType::WorkSheet)
+            .filter(|(_, sheet)| options.include_hidden || sheet.visible == SheetVisible::Visible)
+            .filter(|(index, sheet)| {
+                filters.is_empty() || filters.iter().any(|f| f.matches(*index, &sheet.name))
+            })
+            .map(|(_, sheet)| sheet.name.clone())
+            .collect())
+    }
+
+    /// 预编译工作表筛选条件中的正则表达式
+    fn compile_filters(filters: &[SheetFilter]) -> Result<Vec<CompiledFilter>, String> {
+        filters
+            .iter()
+            .map(|filter| match filter {
+                SheetFilter::Name(name) => Ok(CompiledFilter::Name(name.clone())),
+                SheetFilter::Index(index) => Ok(CompiledFilter::Index(*index)),
+                SheetFilter::Regex(pattern) => Regex::new(pattern)
+                    .map(CompiledFilter::Regex)
+                    .map_err(|e| format!("工作表名称正则表达式无效 {}: {}", pattern, e)),
+            })
+            .collect()
+    }
+
+    /// 裁剪四周全为空的行和列，同步调整原始值与合并区域
+    ///
+    /// # 返回
+    /// 被裁掉的顶部行数和左侧列数
+    fn trim_empty_borders(
+        data: &mut Vec<Vec<String>>,
+        raw: &mut Vec<Vec<CellValue>>,
+        merges: &mut Vec<MergedRange>,
+    ) -> (usize, usize) {
+        let is_blank = |cell: &String| cell.trim().is_empty();
+
+        let non_empty_rows: Vec<usize> = data
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| !row.iter().all(is_blank))
+            .map(|(i, _)| i)
+            .collect();
+
+        let (Some(&top), Some(&bottom)) = (non_empty_rows.first(), non_empty_rows.last()) else {
+            data.clear();
+            raw.clear();
+            merges.clear();
+            return (0, 0);
+        };
+
+        let width = data.iter().map(|row| row.len()).max().unwrap_or(0);
+        let column_used = |c: usize| data.iter().any(|row| row.get(c).is_some_and(|v| !is_blank(v)));
+        let left = (0..width).find(|&c| column_used(c)).unwrap_or(0);
+        let right = (0..width).rev().find(|&c| column_used(c)).unwrap_or(0);
+
+        fn crop<T>(rows: &mut Vec<Vec<T>>, top: usize, bottom: usize, left: usize, right: usize) {
+            rows.truncate(bottom + 1);
+            rows.drain(..top);
+            for row in rows.iter_mut() {
+                row.truncate(right + 1);
+                row.drain(..left.min(row.len()));
+            }
+        }
+
+        crop(data, top, bottom, left, right);
+        crop(raw, top, bottom, left, right);
+
+        // 合并区域平移后裁剪到新的边界内
+        merges.retain_mut(|merge| {
+            let row_end = (merge.row + merge.row_span - 1).min(bottom);
+            let col_end = (merge.col + merge.col_span - 1).min(right);
+            let row_start = merge.row.max(top);
+            let col_start = merge.col.max(left);
+            if row_start > row_end || col_start > col_end {
+                return false;
+            }
+            *merge = MergedRange {
+                row: row_start - top,
+                col: col_start - left,
+                row_span: row_end - row_start + 1,
+                col_span: col_end - col_start + 1,
+            };
+            merge.row_span > 1 || merge.col_span > 1
+        });
+
+        (top, left)
+    }
+
+    /// 根据文件头识别工作簿格式
+    ///
+    /// ZIP 容器需进一步查看内部条目：ODS 有 mimetype 声明，
+    /// xlsb 的工作簿部件为 xl/workbook.bin，xlsx 为 xl/workbook.xml
+    pub fn detect_format(file_path: &str) -> Result<WorkbookFormat, String> {
+        let mut file = File::open(file_path).map_err(|e| e.to_string())?;
+
+        let mut header = [0u8; 8];
+        let len = file.read(&mut header).map_err(|e| e.to_string())?;
+        let header = &header[..len];
+
+        if header.starts_with(CFB_MAGIC) {
+            return Ok(WorkbookFormat::Xls);
+        }
+
+        if !header.starts_with(ZIP_MAGIC) {
+            return Err("无法识别的工作簿格式：文件既不是 ZIP 容器也不是 OLE 复合文档".to_string());
+        }
+
+        let file = File::open(file_path).map_err(|e| e.to_string())?;
+        let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+
+        if let Ok(mut entry) = archive.by_name("mimetype") {
+            let mut mimetype = String::new();
+            entry.read_to_string(&mut mimetype).map_err(|e| e.to_string())?;
+            if mimetype.trim() == "application/vnd.oasis.opendocument.spreadsheet" {
+                return Ok(WorkbookFormat::Ods);
+            }
+            return Err(format!("不支持的 OpenDocument 类型: {}", mimetype.trim()));
+        }
+
+        // 部件名不区分大小写
+        let names: Vec<String> = archive.file_names().map(|n| n.to_ascii_lowercase()).collect();
+
+        if names.iter().any(|n| n == "xl/workbook.bin") {
+            Ok(WorkbookFormat::Xlsb)
+        } else if names.iter().any(|n| n == "xl/workbook.xml") {
+            Ok(WorkbookFormat::Xlsx)
+        } else {
+            Err("ZIP 文件中未找到 Excel 工作簿部件".to_string())
+        }
+    }
+
+    /// 按指定格式打开工作簿
+    pub fn open_workbook(
+        file_path: &str,
+        format: WorkbookFormat,
+    ) -> Result<Sheets<BufReader<File>>, String> {
+        let reader = BufReader::new(File::open(file_path).map_err(|e| e.to_string())?);
+
+        let workbook = match format {
+            WorkbookFormat::Xlsx => Sheets::Xlsx(
+                open_workbook_from_rs::<Xlsx<_>, _>(reader).map_err(|e| e.to_string())?,
+            ),
+            WorkbookFormat::Xlsb => Sheets::Xlsb(
+                open_workbook_from_rs::<Xlsb<_>, _>(reader).map_err(|e| e.to_string())?,
+            ),
+            WorkbookFormat::Xls => Sheets::Xls(
+                open_workbook_from_rs::<Xls<_>, _>(reader).map_err(|e| e.to_string())?,
+            ),
+            WorkbookFormat::Ods => Sheets::Ods(
+                open_workbook_from_rs::<Ods<_>, _>(reader).map_err(|e| e.to_string())?,
+            ),
+        };
+
+        Ok(workbook)
     }
 
     /// 将 calamine Range 转换为字符串矩阵
-    fn range_to_vec(range: &Range<DataType>) -> Vec<Vec<String>> {
+    fn range_to_vec(range: &Range<Data>, sheet: &str, formats: &NumberFormats) -> Vec<Vec<String>> {
+        // Range 的行列从已使用区域左上角开始，格式表使用绝对坐标
+        let (start_row, start_col) = range.start().unwrap_or((0, 0));
+
         range
             .rows()
-            .map(|row| {
+            .enumerate()
+            .map(|(r, row)| {
                 row.iter()
-                    .map(|cell| Self::cell_to_string(cell))
+                    .enumerate()
+                    .map(|(c, cell)| {
+                        let format = formats.get(sheet, start_row + r as u32, start_col + c as u32);
+                        Self::cell_to_string(cell, format)
+                    })
                     .collect()
             })
             .collect()
     }
 
-    /// 将单元格转换为字符串
-    fn cell_to_string(cell: &DataType) -> String {
-        match cell {
-            DataType::Empty => String::new(),
-            DataType::String(s) => s.clone(),
-            DataType::Float(n) => n.to_string(),
-            DataType::Int(n) => n.to_string(),
-            DataType::Bool(b) => b.to_string(),
-            DataType::Error(e) => format!("#ERROR: {}", e),
+    /// 将 calamine Range 转换为带类型的原始值矩阵
+    fn range_to_raw(range: &Range<Data>) -> Vec<Vec<CellValue>> {
+        range
+            .rows()
+            .map(|row| row.iter().map(excel_format::raw_value).collect())
+            .collect()
+    }
+
+    /// 将单元格按数字格式转换为字符串
+    fn cell_to_string(cell: &Data, format: Option<&str>) -> String {
+        excel_format::format_cell(cell, format)
+    }
+
+    /// 将工作表转换为表格
+    ///
+    /// # 参数
+    /// * `sheet` - 工作表数据
+    /// * `split_blocks` - 是否按连通的非空区域拆分为多个表格
+    ///
+    /// # 返回
+    /// 表格列表，每个表格记录所在工作表和单元格区域
+    pub fn sheet_to_tables(sheet: SheetData, split_blocks: bool) -> Vec<ExtractedTable> {
+        let (origin_row, origin_col) = sheet.origin;
+
+        let blocks = if split_blocks {
+            Self::find_blocks(&sheet.data, &sheet.merges)
+        } else {
+            let width = sheet.data.iter().map(|row| row.len()).max().unwrap_or(0);
+            if sheet.data.is_empty() || width == 0 {
+                Vec::new()
+            } else {
+                vec![(0, 0, sheet.data.len() - 1, width - 1)]
+            }
+        };
+
+        blocks
+            .into_iter()
+            .map(|(top, left, bottom, right)| {
+                let crop = |rows: &[Vec<String>]| -> Vec<Vec<String>> {
+                    rows[top..=bottom]
+                        .iter()
+                        .map(|row| (left..=right).map(|c| row.get(c).cloned().unwrap_or_default()).collect())
+                        .collect()
+                };
+
+                let raw: Vec<Vec<CellValue>> = sheet.raw[top..=bottom]
+                    .iter()
+                    .map(|row| (left..=right).map(|c| row.get(c).cloned().unwrap_or(CellValue::Empty)).collect())
+                    .collect();
+
+                let merges = sheet
+                    .merges
+                    .iter()
+                    .filter(|m| m.row >= top && m.col >= left && m.row <= bottom && m.col <= right)
+                    .map(|m| MergedRange {
+                        row: m.row - top,
+                        col: m.col - left,
+                        row_span: m.row_span.min(bottom - m.row + 1),
+                        col_span: m.col_span.min(right - m.col + 1),
+                    })
+                    .collect();
+
+                let formulas = sheet
+                    .formulas
+                    .iter()
+                    .filter(|f| f.row >= top && f.row <= bottom && f.col >= left && f.col <= right)
+                    .map(|f| FormulaCell {
+                        row: f.row - top,
+                        col: f.col - left,
+                        ..f.clone()
+                    })
+                    .collect();
+
+                ExtractedTable {
+                    rows: crop(&sheet.data),
+                    raw_rows: Some(raw),
+                    merges,
+                    formulas,
+                    source: TableSource {
+                        sheet: Some(sheet.name.clone()),
+                        range: Some(excel_format::range_ref(
+                            origin_row + top as u32,
+                            origin_col + left as u32,
+                            origin_row + bottom as u32,
+                            origin_col + right as u32,
+                        )),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    /// 查找工作表中相互独立的表格区域
+    ///
+    /// 非空单元格按八邻域连通（合并区域整体视为非空），
+    /// 再合并外接矩形相互重叠的区域，避免表格内部空单元格把一个表格拆开
+    ///
+    /// # 返回
+    /// 按从上到下、从左到右排序的区域 (上, 左, 下, 右)，均为包含边界
+    fn find_blocks(data: &[Vec<String>], merges: &[MergedRange]) -> Vec<(usize, usize, usize, usize)> {
+        let height = data.len();
+        let width = data.iter().map(|row| row.len()).max().unwrap_or(0);
+
+        let mut occupied = vec![vec![false; width]; height];
+        for (r, row) in data.iter().enumerate() {
+            for (c, cell) in row.iter().enumerate() {
+                occupied[r][c] = !cell.trim().is_empty();
+            }
+        }
+        for merge in merges {
+            let anchor = data.get(merge.row).and_then(|row| row.get(merge.col));
+            if anchor.is_some_and(|v| !v.trim().is_empty()) {
+                for row in occupied.iter_mut().skip(merge.row).take(merge.row_span) {
+                    for cell in row.iter_mut().skip(merge.col).take(merge.col_span) {
+                        *cell = true;
+                    }
+                }
+            }
         }
+
+        let mut visited = vec![vec![false; width]; height];
+        let mut blocks = Vec::new();
+
+        for r in 0..height {
+            for c in 0..width {
+                if !occupied[r][c] || visited[r][c] {
+                    continue;
+                }
+
+                let mut bounds = (r, c, r, c);
+                let mut stack = vec![(r, c)];
+                visited[r][c] = true;
+
+                while let Some((y, x)) = stack.pop() {
+                    bounds = (bounds.0.min(y), bounds.1.min(x), bounds.2.max(y), bounds.3.max(x));
+
+                    for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                        for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                            if occupied[ny][nx] && !visited[ny][nx] {
+                                visited[ny][nx] = true;
+                                stack.push((ny, nx));
+                            }
+                        }
+                    }
+                }
+
+                blocks.push(bounds);
+            }
+        }
+
+        // 合并外接矩形重叠的区域，直到不再变化
+        let overlaps = |a: &(usize, usize, usize, usize), b: &(usize, usize, usize, usize)| {
+            a.0 <= b.2 && b.0 <= a.2 && a.1 <= b.3 && b.1 <= a.3
+        };
+        loop {
+            let mut merged = false;
+            'outer: for i in 0..blocks.len() {
+                for j in i + 1..blocks.len() {
+                    if overlaps(&blocks[i], &blocks[j]) {
+                        let b = blocks.remove(j);
+                        let a = &mut blocks[i];
+                        *a = (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3));
+                        merged = true;
+                        break 'outer;
+                    }
+                }
+            }
+            if !merged {
+                break;
+            }
+        }
+
+        blocks.sort();
+        blocks
     }
 
     /// 提取第一个工作表作为表格
     pub fn extract_first_sheet(file_path: &str) -> Result<ExtractedTable, String> {
         let data = Self::extract_all(file_path)?;
 
-        if let Some(sheet) = data.sheets.first() {
-            Ok(ExtractedTable {
-                rows: sheet.data.clone(),
-            })
-        } else {
-            Ok(ExtractedTable { rows: Vec::new() })
-        }
+        let table = data
+            .sheets
+            .into_iter()
+            .next()
+            .and_then(|sheet| Self::sheet_to_tables(sheet, false).into_iter().next());
+
+        Ok(table.unwrap_or_default())
     }
 
     /// 合并所有工作表为单个表格
@@ -94,6 +741,6 @@ impl ExcelProcessor {
             all_rows.extend(sheet.data.clone());
         }
 
-        Ok(ExtractedTable { rows: all_rows })
+        Ok(ExtractedTable { rows: all_rows, ..Default::default() })
     }
 }
@@ -0,0 +1,544 @@
+// Excel 数字格式模块 - 按单元格数字格式渲染显示文本
+// calamine 只能推断日期类型，不提供格式代码；xlsx 工作簿直接读取 styles.xml 与工作表 XML 获取格式代码，
+// 其他格式（xls / xlsb / ods）仅依据 calamine 推断的值类型渲染
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use calamine::{Data, ExcelDateTime};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use quick_xml::events::{BytesStart, Event};
+use crate::ocr::CellValue;
+
+/// Excel 能精确表示的有效数字位数
+const SIGNIFICANT_DIGITS: i32 = 15;
+
+/// 工作簿中各单元格的数字格式代码
+#[derive(Debug, Default)]
+pub struct NumberFormats {
+    sheets: HashMap<String, HashMap<(u32, u32), String>>,  // 工作表名 → (行, 列) → 格式代码
+}
+
+impl NumberFormats {
+    /// 读取 xlsx 工作簿中所有非“常规”格式的单元格
+    ///
+    /// # 参数
+    /// * `file_path` - xlsx 文件路径
+    pub fn load_xlsx(file_path: &str) -> Result<Self, String> {
+        let file = File::open(file_path).map_err(|e| e.to_string())?;
+        let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+
+        let style_formats = Self::read_styles(&mut archive)?;
+        let sheet_paths = Self::read_sheet_paths(&mut archive)?;
+
+        let mut sheets = HashMap::new();
+
+        for (name, path) in sheet_paths {
+            let cells = Self::read_sheet_styles(&mut archive, &path, &style_formats)?;
+            sheets.insert(name, cells);
+        }
+
+        Ok(Self { sheets })
+    }
+
+    /// 获取单元格格式代码（绝对行列坐标，从 0 开始）
+    pub fn get(&self, sheet: &str, row: u32, col: u32) -> Option<&str> {
+        self.sheets.get(sheet)?.get(&(row, col)).map(|s| s.as_str())
+    }
+
+    /// 读取 styles.xml，返回 cellXfs 索引 → 格式代码
+    fn read_styles<R: Read + Seek>(
+        archive: &mut zip::ZipArchive<R>,
+    ) -> Result<Vec<Option<String>>, String> {
+        let Some(mut reader) = xml_reader(archive, "xl/styles.xml")? else {
+            return Ok(Vec::new());
+        };
+
+        let mut custom: HashMap<u32, String> = HashMap::new();
+        let mut formats = Vec::new();
+        let mut in_cell_xfs = false;
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            match reader.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+                Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                    b"numFmt" => {
+                        let id = attr(&e, b"numFmtId").and_then(|v| v.parse().ok());
+                        if let (Some(id), Some(code)) = (id, attr(&e, b"formatCode")) {
+                            custom.insert(id, code);
+                        }
+                    }
+                    b"cellXfs" => in_cell_xfs = true,
+                    b"xf" if in_cell_xfs => {
+                        let id: u32 = attr(&e, b"numFmtId").and_then(|v| v.parse().ok()).unwrap_or(0);
+                        let code = custom
+                            .get(&id)
+                            .cloned()
+                            .or_else(|| builtin_format(id).map(|s| s.to_string()));
+                        formats.push(code);
+                    }
+                    _ => {}
+                },
+                Event::End(e) if e.local_name().as_ref() == b"cellXfs" => in_cell_xfs = false,
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(formats)
+    }
+
+    /// 读取 workbook.xml 与其关系文件，返回 (工作表名, 部件路径)
+    fn read_sheet_paths<R: Read + Seek>(
+        archive: &mut zip::ZipArchive<R>,
+    ) -> Result<Vec<(String, String)>, String> {
+        let mut targets: HashMap<String, String> = HashMap::new();
+
+        if let Some(mut reader) = xml_reader(archive, "xl/_rels/workbook.xml.rels")? {
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+                    Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                        if let (Some(id), Some(target)) = (attr(&e, b"Id"), attr(&e, b"Target")) {
+                            let path = match target.strip_prefix('/') {
+                                Some(absolute) => absolute.to_string(),
+                                None => format!("xl/{}", target),
+                            };
+                            targets.insert(id, path);
+                        }
+                    }
+                    Event::Eof => break,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut sheets = Vec::new();
+
+        if let Some(mut reader) = xml_reader(archive, "xl/workbook.xml")? {
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+                    Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                        let path = attr(&e, b"id").and_then(|id| targets.get(&id).cloned());
+                        if let (Some(name), Some(path)) = (attr(&e, b"name"), path) {
+                            sheets.push((name, path));
+                        }
+                    }
+                    Event::Eof => break,
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(sheets)
+    }
+
+    /// 读取工作表中各单元格的样式索引，只保留带格式代码的单元格
+    fn read_sheet_styles<R: Read + Seek>(
+        archive: &mut zip::ZipArchive<R>,
+        path: &str,
+        style_formats: &[Option<String>],
+    ) -> Result<HashMap<(u32, u32), String>, String> {
+        let mut cells = HashMap::new();
+
+        let Some(mut reader) = xml_reader(archive, path)? else {
+            return Ok(cells);
+        };
+
+        // 省略 r 属性的行/单元格按顺序递推坐标
+        let mut row: u32 = 0;
+        let mut col: u32 = 0;
+        let mut first_row = true;
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            match reader.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+                Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                    b"row" => {
+                        row = match attr(&e, b"r").and_then(|r| r.parse::<u32>().ok()) {
+                            Some(r) => r.saturating_sub(1),
+                            None if first_row => 0,
+                            None => row + 1,
+                        };
+                        first_row = false;
+                        col = 0;
+                    }
+                    b"c" => {
+                        if let Some((r, c)) = attr(&e, b"r").and_then(|r| parse_cell_ref(&r)) {
+                            row = r;
+                            col = c;
+                        }
+                        let style = attr(&e, b"s").and_then(|s| s.parse::<usize>().ok());
+                        if let Some(Some(code)) = style.and_then(|s| style_formats.get(s)) {
+                            cells.insert((row, col), code.clone());
+                        }
+                        col += 1;
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(cells)
+    }
+}
+
+/// 读入内存的 XML 部件
+type PartReader = quick_xml::Reader<BufReader<std::io::Cursor<Vec<u8>>>>;
+
+/// 打开压缩包中的 XML 部件，部件不存在时返回 None
+fn xml_reader<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    path: &str,
+) -> Result<Option<PartReader>, String> {
+    // 部件名不区分大小写
+    let Some(name) = archive
+        .file_names()
+        .find(|n| n.eq_ignore_ascii_case(path))
+        .map(|n| n.to_string())
+    else {
+        return Ok(None);
+    };
+
+    let mut bytes = Vec::new();
+    archive
+        .by_name(&name)
+        .map_err(|e| e.to_string())?
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+
+    let mut reader = quick_xml::Reader::from_reader(BufReader::new(std::io::Cursor::new(bytes)));
+    reader.trim_text(true);
+    Ok(Some(reader))
+}
+
+/// 按本地名读取属性值（忽略命名空间前缀）
+fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name)
+        .and_then(|a| {
+            let value = String::from_utf8_lossy(&a.value).into_owned();
+            quick_xml::escape::unescape(&value).ok().map(|v| v.into_owned())
+        })
+}
+
+/// 将从 0 开始的列号转换为列字母（0 → `A`，27 → `AB`）
+pub fn column_name(col: u32) -> String {
+    let mut name = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        name.push(b'A' + rem as u8);
+        n = (n - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// 将从 0 开始的行列坐标区域转换为 A1 引用（如 `B3:H20`）
+pub fn range_ref(top: u32, left: u32, bottom: u32, right: u32) -> String {
+    let start = format!("{}{}", column_name(left), top + 1);
+    if top == bottom && left == right {
+        start
+    } else {
+        format!("{}:{}{}", start, column_name(right), bottom + 1)
+    }
+}
+
+/// 解析单元格引用（如 `B3`）为从 0 开始的 (行, 列)
+pub fn parse_cell_ref(reference: &str) -> Option<(u32, u32)> {
+    let split = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+
+    if letters.is_empty() {
+        return None;
+    }
+
+    let mut col: u32 = 0;
+    for c in letters.chars() {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        col = col * 26 + (c.to_ascii_uppercase() as u32 - 'A' as u32 + 1);
+    }
+
+    let row: u32 = digits.parse().ok()?;
+    Some((row.checked_sub(1)?, col - 1))
+}
+
+/// 内置数字格式代码（ECMA-376 18.8.30）
+pub fn builtin_format(id: u32) -> Option<&'static str> {
+    let code = match id {
+        1 => "0",
+        2 => "0.00",
+        3 => "#,##0",
+        4 => "#,##0.00",
+        9 => "0%",
+        10 => "0.00%",
+        11 => "0.00E+00",
+        14 => "yyyy-mm-dd",
+        15 => "d-mmm-yy",
+        16 => "d-mmm",
+        17 => "mmm-yy",
+        18 => "h:mm AM/PM",
+        19 => "h:mm:ss AM/PM",
+        20 => "h:mm",
+        21 => "h:mm:ss",
+        22 => "yyyy-mm-dd h:mm",
+        37 => "#,##0 ;(#,##0)",
+        38 => "#,##0 ;[Red](#,##0)",
+        39 => "#,##0.00;(#,##0.00)",
+        40 => "#,##0.00;[Red](#,##0.00)",
+        45 => "mm:ss",
+        46 => "[h]:mm:ss",
+        47 => "mmss.0",
+        48 => "##0.0E+0",
+        49 => "@",
+        // 中文区域设置的日期格式
+        27..=36 | 50..=58 => "yyyy-mm-dd",
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// 按数字格式渲染单元格显示文本
+///
+/// * 日期 → `2023-07-15`，日期时间 → `2023-07-15 08:30:00`，时间 → `08:30:00`
+/// * 百分比 → `12.50%`（小数位数取自格式）
+/// * 补零格式（如 `000000`）→ 保留前导零
+/// * 其他数值 → 不使用科学计数法，最多保留 15 位有效数字
+pub fn format_cell(cell: &Data, format: Option<&str>) -> String {
+    let section = format.map(first_section);
+    let section = section.as_deref();
+
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(s) => s.clone(),
+        Data::Bool(b) => b.to_string(),
+        Data::Int(n) => format_number(*n as f64, section),
+        Data::Float(n) => format_number(*n, section),
+        Data::DateTime(dt) => format_datetime(dt, section),
+        Data::DateTimeIso(s) | Data::DurationIso(s) => s.clone(),
+        Data::Error(e) => format!("#ERROR: {}", e),
+    }
+}
+
+/// 转换为保留类型信息的原始值
+pub fn raw_value(cell: &Data) -> CellValue {
+    match cell {
+        Data::Empty => CellValue::Empty,
+        Data::String(s) => CellValue::String(s.clone()),
+        Data::Bool(b) => CellValue::Bool(*b),
+        Data::Int(n) => CellValue::Int(*n),
+        Data::Float(n) => CellValue::Float(*n),
+        Data::DateTime(dt) if dt.is_duration() => CellValue::Duration(format_duration(dt.as_f64())),
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(value) => CellValue::DateTime(value.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            None => CellValue::Float(dt.as_f64()),
+        },
+        Data::DateTimeIso(s) => CellValue::DateTime(s.clone()),
+        Data::DurationIso(s) => CellValue::Duration(s.clone()),
+        Data::Error(e) => CellValue::Error(e.to_string()),
+    }
+}
+
+/// 取格式代码的第一节（正数格式），去除引号内的字面文本和颜色、区域设置等方括号修饰
+/// 只保留 [h]、[mm]、[ss] 等耗时格式的方括号
+fn first_section(format: &str) -> String {
+    let mut result = String::new();
+    let mut in_quote = false;
+    let mut bracket: Option<String> = None;
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if let Some(inner) = bracket.as_mut() {
+            if c == ']' {
+                if !inner.is_empty() && inner.chars().all(|c| matches!(c.to_ascii_lowercase(), 'h' | 'm' | 's')) {
+                    result.push_str(&format!("[{}]", inner));
+                }
+                bracket = None;
+            } else {
+                inner.push(c);
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_quote = !in_quote,
+            _ if in_quote => {}
+            '\\' => {
+                chars.next();
+            }
+            '[' => bracket = Some(String::new()),
+            ';' => break,
+            _ => result.push(c),
+        }
+    }
+
+    result.trim().to_string()
+}
+
+/// 格式代码是否为日期/时间格式
+fn is_date_format(format: &str) -> bool {
+    format
+        .chars()
+        .any(|c| matches!(c.to_ascii_lowercase(), 'y' | 'd' | 'h' | 's'))
+        || format.eq_ignore_ascii_case("mmm")
+}
+
+/// 渲染数值
+fn format_number(value: f64, format: Option<&str>) -> String {
+    let Some(format) = format.filter(|f| !f.is_empty() && !f.eq_ignore_ascii_case("General")) else {
+        return general_number(value);
+    };
+
+    // 文本格式
+    if format == "@" {
+        return general_number(value);
+    }
+
+    // 百分比
+    if format.contains('%') {
+        let decimals = decimal_places(format);
+        return format!("{:.*}%", decimals, value * 100.0);
+    }
+
+    // calamine 未能识别的自定义日期格式
+    if is_date_format(format) && !format.contains('#') && !format.contains('0') {
+        return excel_serial_to_datetime(value)
+            .map(|dt| render_datetime(dt, value, Some(format)))
+            .unwrap_or_else(|| general_number(value));
+    }
+
+    // 科学计数法格式保持常规渲染，避免精度丢失
+    if format.to_ascii_uppercase().contains("E+") {
+        return general_number(value);
+    }
+
+    // 补零格式（如 000000），用于身份证号、工号、邮编等
+    let integer_part = format.split('.').next().unwrap_or("");
+    let zero_width = integer_part.chars().filter(|c| *c == '0').count();
+    if !format.contains('.')
+        && zero_width > 1
+        && integer_part.chars().all(|c| c == '0')
+        && value.fract() == 0.0
+        && (0.0..1e15).contains(&value)
+    {
+        return format!("{:0width$}", value as i64, width = zero_width);
+    }
+
+    // 固定小数位
+    if format.contains('.') {
+        let decimals = decimal_places(format);
+        return format!("{:.*}", decimals, value);
+    }
+
+    general_number(value)
+}
+
+/// 格式代码小数点后的占位符个数
+fn decimal_places(format: &str) -> usize {
+    format
+        .split_once('.')
+        .map(|(_, frac)| frac.chars().take_while(|c| matches!(c, '0' | '#' | '?')).count())
+        .unwrap_or(0)
+}
+
+/// 常规格式：整数不带小数，其余最多 15 位有效数字，不使用科学计数法
+fn general_number(value: f64) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        return format!("{}", value as i64);
+    }
+
+    let magnitude = if value == 0.0 { 0 } else { value.abs().log10().floor() as i32 };
+
+    // 超过 15 位的整数部分与 Excel 一致，多出的位数显示为 0
+    if magnitude >= SIGNIFICANT_DIGITS {
+        let digits = format!("{:.0}", value.abs());
+        let keep = SIGNIFICANT_DIGITS as usize;
+        let mut rounded: u64 = digits[..keep].parse::<u64>().unwrap_or(0)
+            + u64::from(digits.as_bytes()[keep] >= b'5');
+        let mut zeros = digits.len() - keep;
+        // 进位后多出一位（999…9 → 1000…0）时去掉末位，指数加一
+        if rounded >= 10u64.pow(SIGNIFICANT_DIGITS as u32) {
+            rounded /= 10;
+            zeros += 1;
+        }
+        let sign = if value < 0.0 { "-" } else { "" };
+        return format!("{}{}{}", sign, rounded, "0".repeat(zeros));
+    }
+
+    let decimals = (SIGNIFICANT_DIGITS - 1 - magnitude).max(0) as usize;
+    let text = format!("{:.*}", decimals, value);
+
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+/// 渲染日期时间单元格
+fn format_datetime(dt: &ExcelDateTime, format: Option<&str>) -> String {
+    if dt.is_duration() {
+        return format_duration(dt.as_f64());
+    }
+
+    match dt.as_datetime() {
+        Some(value) => render_datetime(value, dt.as_f64(), format),
+        None => general_number(dt.as_f64()),
+    }
+}
+
+/// 按格式中是否含日期/时间部分选择输出形式
+fn render_datetime(value: NaiveDateTime, serial: f64, format: Option<&str>) -> String {
+    let (has_date, has_time) = match format {
+        Some(f) if is_date_format(f) => {
+            let lower = f.to_ascii_lowercase();
+            (
+                lower.contains('y') || lower.contains('d') || (lower.contains('m') && !lower.contains('h') && !lower.contains('s')),
+                lower.contains('h') || lower.contains('s'),
+            )
+        }
+        _ => (serial >= 1.0, serial.fract() != 0.0),
+    };
+
+    match (has_date, has_time) {
+        (true, true) => value.format("%Y-%m-%d %H:%M:%S").to_string(),
+        (false, true) => value.format("%H:%M:%S").to_string(),
+        _ => value.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// 耗时渲染为 `[h]:mm:ss`
+fn format_duration(days: f64) -> String {
+    let total = (days * 86400.0).round() as i64;
+    let sign = if total < 0 { "-" } else { "" };
+    let total = total.abs();
+    format!("{}{:02}:{:02}:{:02}", sign, total / 3600, total % 3600 / 60, total % 60)
+}
+
+/// 将 1900 日期系统的序列值转换为日期时间
+fn excel_serial_to_datetime(serial: f64) -> Option<NaiveDateTime> {
+    if !(0.0..2958466.0).contains(&serial) {
+        return None;
+    }
+
+    // 1900 年闰年错误：序列值 60 之前需加一天
+    let base = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_hms_opt(0, 0, 0)?;
+    let serial = if serial < 60.0 { serial + 1.0 } else { serial };
+    let millis = (serial * 86_400_000.0).round() as i64;
+    base.checked_add_signed(Duration::milliseconds(millis))
+}
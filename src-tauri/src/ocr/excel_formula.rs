@@ -0,0 +1,1118 @@
+// Excel 公式模块 - 对缺少缓存值的公式单元格进行安全子集求值
+// 支持算术、比较、文本连接运算，工作簿内的单元格/区域引用，
+// 以及 SUM、AVERAGE、MIN、MAX、COUNT、COUNTA、IF、IFERROR、AND、OR、NOT、ROUND、ABS、CONCATENATE、VLOOKUP；
+// 其他函数返回 #NAME? 错误，不会访问外部链接或执行宏
+
+use std::collections::{HashMap, HashSet};
+use calamine::{CellErrorType, Data, Range};
+use crate::ocr::excel_format::parse_cell_ref;
+
+/// 单个区域引用最多展开的单元格数，超出时返回 #NUM!
+const MAX_RANGE_CELLS: u64 = 1_000_000;
+
+/// 工作表的最大行号和列号（从 0 开始），整行、整列引用展开到此处后再按已用区域裁剪
+const LAST_ROW: u32 = 1_048_575;
+const LAST_COL: u32 = 16_383;
+
+/// 求值结果：工作表名 → (绝对坐标 → 计算结果)
+pub type EvaluatedCells = HashMap<String, HashMap<(u32, u32), Data>>;
+
+/// 公式单元格的键：(工作表名, 行, 列)
+type CellKey = (String, u32, u32);
+
+/// 公式求值使用的工作簿快照
+#[derive(Default)]
+pub struct FormulaBook {
+    sheets: HashMap<String, BookSheet>,
+    cache: HashMap<CellKey, Value>,     // 已求值的公式单元格
+    evaluating: HashSet<CellKey>,       // 正在等待依赖求值的单元格，用于检测循环引用
+}
+
+/// 工作表快照
+struct BookSheet {
+    values: Range<Data>,                      // 缓存值
+    formulas: HashMap<(u32, u32), String>,    // 绝对坐标 → 公式文本（不含前导 =）
+    used_end: Option<(u32, u32)>,             // 有值或公式的最大行列（绝对坐标）
+}
+
+/// 求值过程中的值
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Empty,
+    Error(CellErrorType),
+    Table(Vec<Vec<Value>>),  // 区域引用的值，保留行列结构（供 VLOOKUP 使用）
+}
+
+/// 公式语法树
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Ref { sheet: Option<String>, row: u32, col: u32 },
+    Range { sheet: Option<String>, start: (u32, u32), end: (u32, u32) },
+    Neg(Box<Expr>),
+    Percent(Box<Expr>),
+    Binary(String, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+/// 词法单元
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Ident(String),        // 函数名、TRUE/FALSE、单元格引用
+    Sheet(String),        // 工作表限定前缀（Sheet1! 或 'My Sheet'!）
+    Op(String),
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+}
+
+impl FormulaBook {
+    /// 创建空的工作簿快照
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加工作表的缓存值与公式
+    pub fn add_sheet(&mut self, name: &str, values: Range<Data>, formulas: &Range<String>) {
+        let (start_row, start_col) = formulas.start().unwrap_or((0, 0));
+        let mut map = HashMap::new();
+
+        for (r, c, formula) in formulas.used_cells() {
+            if !formula.is_empty() {
+                map.insert((start_row + r as u32, start_col + c as u32), formula.clone());
+            }
+        }
+
+        let used_end = map
+            .keys()
+            .copied()
+            .chain(values.end())
+            .reduce(|a, b| (a.0.max(b.0), a.1.max(b.1)));
+
+        self.sheets.insert(name.to_string(), BookSheet { values, formulas: map, used_end });
+    }
+
+    /// 计算所有缺少缓存值的公式单元格
+    ///
+    /// 按工作表、行、列的顺序求值，结果与公式在文件中的存储顺序无关
+    pub fn evaluate_missing(&mut self) -> EvaluatedCells {
+        let mut pending: Vec<CellKey> = Vec::new();
+
+        for (name, sheet) in &self.sheets {
+            for &(row, col) in sheet.formulas.keys() {
+                let cached = sheet.values.get_value((row, col));
+                if cached.is_none_or(|v| *v == Data::Empty) {
+                    pending.push((name.clone(), row, col));
+                }
+            }
+        }
+        pending.sort();
+
+        let mut results = EvaluatedCells::new();
+
+        for (sheet, row, col) in pending {
+            let value = self.cell_value(&sheet, row, col);
+            results
+                .entry(sheet)
+                .or_default()
+                .insert((row, col), value_to_data(value));
+        }
+
+        results
+    }
+
+    /// 获取单元格的值，缺少缓存值的公式单元格先求值
+    fn cell_value(&mut self, sheet: &str, row: u32, col: u32) -> Value {
+        let Some(book_sheet) = self.sheets.get(sheet) else {
+            return Value::Error(CellErrorType::Ref);
+        };
+
+        let key = (sheet.to_string(), row, col);
+        if self.pending_formula(&key).is_none() {
+            let cached = book_sheet.values.get_value((row, col)).cloned().unwrap_or(Data::Empty);
+            return data_to_value(&cached);
+        }
+
+        if let Some(value) = self.cache.get(&key) {
+            return value.clone();
+        }
+
+        // 循环引用：单元格仍在等待自身依赖的求值
+        if self.evaluating.contains(&key) {
+            return Value::Error(CellErrorType::Ref);
+        }
+
+        self.resolve(key)
+    }
+
+    /// 缺少缓存值的单元格的公式文本
+    fn pending_formula(&self, (sheet, row, col): &CellKey) -> Option<&str> {
+        let book_sheet = self.sheets.get(sheet)?;
+        let formula = book_sheet.formulas.get(&(*row, *col))?;
+        let cached = book_sheet.values.get_value((*row, *col));
+        cached.is_none_or(|v| *v == Data::Empty).then_some(formula.as_str())
+    }
+
+    /// 用显式栈按依赖顺序求值公式单元格
+    ///
+    /// 单元格第一次出栈时压入尚未求值的依赖，依赖全部求值后再计算自身；
+    /// 引用链的长度不受调用栈深度限制（如逐行累加的万行公式）
+    fn resolve(&mut self, root: CellKey) -> Value {
+        let mut stack = vec![root.clone()];
+
+        while let Some(key) = stack.last().cloned() {
+            if self.cache.contains_key(&key) {
+                stack.pop();
+                continue;
+            }
+
+            let expr = match self.pending_formula(&key).map(parse) {
+                Some(Ok(expr)) => expr,
+                Some(Err(_)) => {
+                    self.cache.insert(key, Value::Error(CellErrorType::Name));
+                    stack.pop();
+                    continue;
+                }
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            };
+
+            // 第一次访问时压入依赖；依赖中仍在等待求值的单元格构成循环引用，不再压栈
+            if self.evaluating.insert(key.clone()) {
+                let mut dependencies = Vec::new();
+                self.dependencies(&expr, &key.0, &mut dependencies);
+                let before = stack.len();
+                stack.extend(dependencies.into_iter().filter(|dep| {
+                    !self.cache.contains_key(dep) && !self.evaluating.contains(dep) && self.pending_formula(dep).is_some()
+                }));
+                if stack.len() > before {
+                    continue;
+                }
+            }
+
+            let value = scalar(self.eval(&expr, &key.0));
+            self.evaluating.remove(&key);
+            self.cache.insert(key, value);
+            stack.pop();
+        }
+
+        self.cache.get(&root).cloned().unwrap_or(Value::Empty)
+    }
+
+    /// 收集表达式引用的公式单元格
+    fn dependencies(&self, expr: &Expr, sheet: &str, out: &mut Vec<CellKey>) {
+        match expr {
+            Expr::Ref { sheet: target, row, col } => {
+                out.push((target.as_deref().unwrap_or(sheet).to_string(), *row, *col));
+            }
+            Expr::Range { sheet: target, start, end } => {
+                let target = target.as_deref().unwrap_or(sheet);
+                let Some(book_sheet) = self.sheets.get(target) else { return };
+                let Ok((top, left, bottom, right)) = self.clip_range(target, *start, *end) else { return };
+
+                // 区域比公式数量大时遍历公式，否则遍历区域
+                let area = (bottom - top + 1) as usize * (right - left + 1) as usize;
+                if area > book_sheet.formulas.len() {
+                    out.extend(
+                        book_sheet
+                            .formulas
+                            .keys()
+                            .filter(|(r, c)| (top..=bottom).contains(r) && (left..=right).contains(c))
+                            .map(|&(r, c)| (target.to_string(), r, c)),
+                    );
+                } else {
+                    for r in top..=bottom {
+                        for c in left..=right {
+                            if book_sheet.formulas.contains_key(&(r, c)) {
+                                out.push((target.to_string(), r, c));
+                            }
+                        }
+                    }
+                }
+            }
+            Expr::Neg(inner) | Expr::Percent(inner) => self.dependencies(inner, sheet, out),
+            Expr::Binary(_, lhs, rhs) => {
+                self.dependencies(lhs, sheet, out);
+                self.dependencies(rhs, sheet, out);
+            }
+            Expr::Call(_, args) => {
+                for arg in args {
+                    self.dependencies(arg, sheet, out);
+                }
+            }
+            Expr::Number(_) | Expr::Text(_) | Expr::Bool(_) => {}
+        }
+    }
+
+    /// 将区域裁剪到工作表的已用区域，返回 (上, 左, 下, 右)
+    ///
+    /// 整列、整行引用只展开到已用区域，起始位置不变（VLOOKUP 按相对列号取值）；
+    /// 工作表不存在时返回 #REF!，裁剪后仍超过单元格上限时返回 #NUM!
+    fn clip_range(&self, sheet: &str, start: (u32, u32), end: (u32, u32)) -> Result<(u32, u32, u32, u32), CellErrorType> {
+        let used_end = self.sheets.get(sheet).ok_or(CellErrorType::Ref)?.used_end;
+
+        let (top, left) = (start.0.min(end.0), start.1.min(end.1));
+        let (used_bottom, used_right) = used_end.unwrap_or((0, 0));
+        let bottom = start.0.max(end.0).min(used_bottom.max(top));
+        let right = start.1.max(end.1).min(used_right.max(left));
+
+        let cells = (bottom - top + 1) as u64 * (right - left + 1) as u64;
+        if cells > MAX_RANGE_CELLS {
+            return Err(CellErrorType::Num);
+        }
+        Ok((top, left, bottom, right))
+    }
+
+    /// 计算表达式
+    fn eval(&mut self, expr: &Expr, sheet: &str) -> Value {
+        match expr {
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Text(s) => Value::Text(s.clone()),
+            Expr::Bool(b) => Value::Bool(*b),
+            Expr::Ref { sheet: target, row, col } => {
+                let target = target.as_deref().unwrap_or(sheet).to_string();
+                self.cell_value(&target, *row, *col)
+            }
+            Expr::Range { sheet: target, start, end } => {
+                let target = target.as_deref().unwrap_or(sheet).to_string();
+                let (top, left, bottom, right) = match self.clip_range(&target, *start, *end) {
+                    Ok(bounds) => bounds,
+                    Err(e) => return Value::Error(e),
+                };
+
+                let mut rows = Vec::new();
+                for r in top..=bottom {
+                    let mut row = Vec::new();
+                    for c in left..=right {
+                        row.push(self.cell_value(&target, r, c));
+                    }
+                    rows.push(row);
+                }
+                Value::Table(rows)
+            }
+            Expr::Neg(inner) => match to_number(&scalar(self.eval(inner, sheet))) {
+                Ok(n) => Value::Number(-n),
+                Err(e) => Value::Error(e),
+            },
+            Expr::Percent(inner) => match to_number(&scalar(self.eval(inner, sheet))) {
+                Ok(n) => Value::Number(n / 100.0),
+                Err(e) => Value::Error(e),
+            },
+            Expr::Binary(op, lhs, rhs) => {
+                let left = scalar(self.eval(lhs, sheet));
+                let right = scalar(self.eval(rhs, sheet));
+                binary(op, &left, &right)
+            }
+            Expr::Call(name, args) => self.call(name, args, sheet),
+        }
+    }
+
+    /// 调用函数
+    fn call(&mut self, name: &str, args: &[Expr], sheet: &str) -> Value {
+        match name {
+            // IF / IFERROR 只计算需要的分支
+            "IF" => {
+                if args.is_empty() || args.len() > 3 {
+                    return Value::Error(CellErrorType::Value);
+                }
+                let condition = scalar(self.eval(&args[0], sheet));
+                match to_bool(&condition) {
+                    Ok(true) => args.get(1).map_or(Value::Bool(true), |e| scalar(self.eval(e, sheet))),
+                    Ok(false) => args.get(2).map_or(Value::Bool(false), |e| scalar(self.eval(e, sheet))),
+                    Err(e) => Value::Error(e),
+                }
+            }
+            "IFERROR" => {
+                if args.len() != 2 {
+                    return Value::Error(CellErrorType::Value);
+                }
+                match scalar(self.eval(&args[0], sheet)) {
+                    Value::Error(_) => scalar(self.eval(&args[1], sheet)),
+                    value => value,
+                }
+            }
+            _ => {
+                let values: Vec<Value> = args.iter().map(|a| self.eval(a, sheet)).collect();
+                call_function(name, values)
+            }
+        }
+    }
+}
+
+/// 调用无需惰性求值的函数
+fn call_function(name: &str, args: Vec<Value>) -> Value {
+    match name {
+        "SUM" => numbers(&args).map_or_else(Value::Error, |ns| Value::Number(ns.iter().sum())),
+        "AVERAGE" => match numbers(&args) {
+            Ok(ns) if ns.is_empty() => Value::Error(CellErrorType::Div0),
+            Ok(ns) => Value::Number(ns.iter().sum::<f64>() / ns.len() as f64),
+            Err(e) => Value::Error(e),
+        },
+        "MIN" | "MAX" => match numbers(&args) {
+            Ok(ns) if ns.is_empty() => Value::Number(0.0),
+            Ok(ns) if name == "MIN" => Value::Number(ns.iter().cloned().fold(f64::INFINITY, f64::min)),
+            Ok(ns) => Value::Number(ns.iter().cloned().fold(f64::NEG_INFINITY, f64::max)),
+            Err(e) => Value::Error(e),
+        },
+        "COUNT" => Value::Number(
+            flatten(&args).iter().filter(|v| matches!(v, Value::Number(_))).count() as f64,
+        ),
+        "COUNTA" => Value::Number(
+            flatten(&args).iter().filter(|v| !matches!(v, Value::Empty)).count() as f64,
+        ),
+        "AND" | "OR" => {
+            let mut result = name == "AND";
+            for value in flatten(&args) {
+                if matches!(value, Value::Empty | Value::Text(_)) {
+                    continue;
+                }
+                match to_bool(&value) {
+                    Ok(b) if name == "AND" => result &= b,
+                    Ok(b) => result |= b,
+                    Err(e) => return Value::Error(e),
+                }
+            }
+            Value::Bool(result)
+        }
+        "NOT" => match args.as_slice() {
+            [value] => to_bool(&scalar(value.clone())).map_or_else(Value::Error, |b| Value::Bool(!b)),
+            _ => Value::Error(CellErrorType::Value),
+        },
+        "ABS" => match args.as_slice() {
+            [value] => to_number(&scalar(value.clone())).map_or_else(Value::Error, |n| Value::Number(n.abs())),
+            _ => Value::Error(CellErrorType::Value),
+        },
+        "ROUND" => match args.as_slice() {
+            [value, digits] => {
+                let value = to_number(&scalar(value.clone()));
+                let digits = to_number(&scalar(digits.clone()));
+                match (value, digits) {
+                    (Ok(v), Ok(d)) => {
+                        let factor = 10f64.powi(d.trunc() as i32);
+                        Value::Number((v * factor).round() / factor)
+                    }
+                    (Err(e), _) | (_, Err(e)) => Value::Error(e),
+                }
+            }
+            _ => Value::Error(CellErrorType::Value),
+        },
+        "CONCATENATE" => {
+            let mut text = String::new();
+            for value in args {
+                match to_text(&scalar(value)) {
+                    Ok(s) => text.push_str(&s),
+                    Err(e) => return Value::Error(e),
+                }
+            }
+            Value::Text(text)
+        }
+        "VLOOKUP" => vlookup(args),
+        _ => Value::Error(CellErrorType::Name),
+    }
+}
+
+/// VLOOKUP(查找值, 区域, 列序号, [近似匹配])
+fn vlookup(args: Vec<Value>) -> Value {
+    if args.len() < 3 || args.len() > 4 {
+        return Value::Error(CellErrorType::Value);
+    }
+
+    let lookup = scalar(args[0].clone());
+    if let Value::Error(e) = lookup {
+        return Value::Error(e);
+    }
+
+    let Value::Table(table) = &args[1] else {
+        return Value::Error(CellErrorType::NA);
+    };
+
+    let column = match to_number(&scalar(args[2].clone())) {
+        Ok(n) if n >= 1.0 => n as usize - 1,
+        Ok(_) => return Value::Error(CellErrorType::Value),
+        Err(e) => return Value::Error(e),
+    };
+    if table.first().is_none_or(|row| column >= row.len()) {
+        return Value::Error(CellErrorType::Ref);
+    }
+
+    let approximate = match args.get(3) {
+        Some(value) => match to_bool(&scalar(value.clone())) {
+            Ok(b) => b,
+            Err(e) => return Value::Error(e),
+        },
+        None => true,
+    };
+
+    let found = if approximate {
+        // 区域按首列升序排列，取不大于查找值的最后一行；跳过空单元格和类型不同的行（如文本表头）
+        let mut found = None;
+        for row in table.iter().filter(|row| !matches!(row[0], Value::Empty)) {
+            match compare(&row[0], &lookup) {
+                Some(std::cmp::Ordering::Greater) => break,
+                Some(_) => found = Some(row),
+                None => {}
+            }
+        }
+        found
+    } else {
+        table
+            .iter()
+            .find(|row| compare(&row[0], &lookup) == Some(std::cmp::Ordering::Equal))
+    };
+
+    found.map_or(Value::Error(CellErrorType::NA), |row| row[column].clone())
+}
+
+/// 计算二元运算
+fn binary(op: &str, left: &Value, right: &Value) -> Value {
+    if let Value::Error(e) = left {
+        return Value::Error(e.clone());
+    }
+    if let Value::Error(e) = right {
+        return Value::Error(e.clone());
+    }
+
+    match op {
+        "&" => match (to_text(left), to_text(right)) {
+            (Ok(a), Ok(b)) => Value::Text(a + &b),
+            (Err(e), _) | (_, Err(e)) => Value::Error(e),
+        },
+        "=" | "<>" | "<" | ">" | "<=" | ">=" => {
+            let ordering = compare(left, right).unwrap_or_else(|| type_rank(left).cmp(&type_rank(right)));
+            use std::cmp::Ordering::*;
+            Value::Bool(match op {
+                "=" => ordering == Equal,
+                "<>" => ordering != Equal,
+                "<" => ordering == Less,
+                ">" => ordering == Greater,
+                "<=" => ordering != Greater,
+                _ => ordering != Less,
+            })
+        }
+        _ => {
+            let (a, b) = match (to_number(left), to_number(right)) {
+                (Ok(a), Ok(b)) => (a, b),
+                (Err(e), _) | (_, Err(e)) => return Value::Error(e),
+            };
+            let result = match op {
+                "+" => a + b,
+                "-" => a - b,
+                "*" => a * b,
+                "/" if b == 0.0 => return Value::Error(CellErrorType::Div0),
+                "/" => a / b,
+                "^" => a.powf(b),
+                _ => return Value::Error(CellErrorType::Value),
+            };
+            if result.is_finite() {
+                Value::Number(result)
+            } else {
+                Value::Error(CellErrorType::Num)
+            }
+        }
+    }
+}
+
+/// 比较同类值（文本不区分大小写），类型不同时返回 None
+fn compare(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.partial_cmp(y),
+        (Value::Text(x), Value::Text(y)) => Some(x.to_lowercase().cmp(&y.to_lowercase())),
+        (Value::Bool(x), Value::Bool(y)) => Some(x.cmp(y)),
+        (Value::Empty, Value::Empty) => Some(std::cmp::Ordering::Equal),
+        (Value::Empty, other) | (other, Value::Empty) => {
+            let blank = match other {
+                Value::Number(_) => Value::Number(0.0),
+                Value::Text(_) => Value::Text(String::new()),
+                Value::Bool(_) => Value::Bool(false),
+                _ => return None,
+            };
+            if matches!(a, Value::Empty) { compare(&blank, other) } else { compare(other, &blank) }
+        }
+        _ => None,
+    }
+}
+
+/// Excel 跨类型比较顺序：数值 < 文本 < 布尔
+fn type_rank(value: &Value) -> u8 {
+    match value {
+        Value::Number(_) | Value::Empty => 0,
+        Value::Text(_) => 1,
+        Value::Bool(_) => 2,
+        _ => 3,
+    }
+}
+
+/// 区域值在标量上下文中取左上角单元格
+fn scalar(value: Value) -> Value {
+    match value {
+        Value::Table(rows) => rows.into_iter().flatten().next().unwrap_or(Value::Empty),
+        value => value,
+    }
+}
+
+/// 展开区域参数
+fn flatten(args: &[Value]) -> Vec<Value> {
+    let mut values = Vec::new();
+    for arg in args {
+        match arg {
+            Value::Table(rows) => values.extend(rows.iter().flatten().cloned()),
+            value => values.push(value.clone()),
+        }
+    }
+    values
+}
+
+/// 收集聚合函数的数值参数：区域中的文本、布尔和空单元格被忽略，直接参数按数值转换
+fn numbers(args: &[Value]) -> Result<Vec<f64>, CellErrorType> {
+    let mut result = Vec::new();
+    for arg in args {
+        match arg {
+            Value::Table(_) => {
+                for value in flatten(std::slice::from_ref(arg)) {
+                    match value {
+                        Value::Number(n) => result.push(n),
+                        Value::Error(e) => return Err(e),
+                        _ => {}
+                    }
+                }
+            }
+            Value::Empty => {}
+            value => result.push(to_number(value)?),
+        }
+    }
+    Ok(result)
+}
+
+/// 转换为数值
+fn to_number(value: &Value) -> Result<f64, CellErrorType> {
+    match value {
+        Value::Number(n) => Ok(*n),
+        Value::Bool(b) => Ok(if *b { 1.0 } else { 0.0 }),
+        Value::Empty => Ok(0.0),
+        Value::Text(s) => s.trim().parse().map_err(|_| CellErrorType::Value),
+        Value::Error(e) => Err(e.clone()),
+        Value::Table(_) => to_number(&scalar(value.clone())),
+    }
+}
+
+/// 转换为布尔值
+fn to_bool(value: &Value) -> Result<bool, CellErrorType> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        Value::Number(n) => Ok(*n != 0.0),
+        Value::Empty => Ok(false),
+        Value::Text(s) if s.eq_ignore_ascii_case("TRUE") => Ok(true),
+        Value::Text(s) if s.eq_ignore_ascii_case("FALSE") => Ok(false),
+        Value::Text(_) => Err(CellErrorType::Value),
+        Value::Error(e) => Err(e.clone()),
+        Value::Table(_) => to_bool(&scalar(value.clone())),
+    }
+}
+
+/// 转换为文本
+fn to_text(value: &Value) -> Result<String, CellErrorType> {
+    match value {
+        Value::Text(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(crate::ocr::excel_format::format_cell(&Data::Float(*n), None)),
+        Value::Bool(b) => Ok(if *b { "TRUE" } else { "FALSE" }.to_string()),
+        Value::Empty => Ok(String::new()),
+        Value::Error(e) => Err(e.clone()),
+        Value::Table(_) => to_text(&scalar(value.clone())),
+    }
+}
+
+/// 单元格缓存值转换为求值用的值
+fn data_to_value(data: &Data) -> Value {
+    match data {
+        Data::Int(n) => Value::Number(*n as f64),
+        Data::Float(n) => Value::Number(*n),
+        Data::DateTime(dt) => Value::Number(dt.as_f64()),
+        Data::String(s) | Data::DateTimeIso(s) | Data::DurationIso(s) => Value::Text(s.clone()),
+        Data::Bool(b) => Value::Bool(*b),
+        Data::Error(e) => Value::Error(e.clone()),
+        Data::Empty => Value::Empty,
+    }
+}
+
+/// 求值结果转换为单元格值
+fn value_to_data(value: Value) -> Data {
+    match scalar(value) {
+        Value::Number(n) => Data::Float(n),
+        Value::Text(s) => Data::String(s),
+        Value::Bool(b) => Data::Bool(b),
+        Value::Error(e) => Data::Error(e),
+        _ => Data::Empty,
+    }
+}
+
+/// 解析公式文本（可带前导 =）
+fn parse(formula: &str) -> Result<Expr, String> {
+    let tokens = tokenize(formula.trim().trim_start_matches('='))?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.comparison()?;
+    if parser.pos != parser.tokens.len() {
+        return Err(format!("公式中有多余内容: {}", formula));
+    }
+    Ok(expr)
+}
+
+/// 词法分析
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\n' | '\r' => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' | ';' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            ':' => {
+                tokens.push(Token::Colon);
+                i += 1;
+            }
+            '+' | '-' | '*' | '/' | '^' | '&' | '=' | '%' => {
+                tokens.push(Token::Op(c.to_string()));
+                i += 1;
+            }
+            '<' | '>' => {
+                let next = chars.get(i + 1).copied();
+                let op = match (c, next) {
+                    ('<', Some('=')) | ('>', Some('=')) | ('<', Some('>')) => {
+                        i += 2;
+                        format!("{}{}", c, next.unwrap_or_default())
+                    }
+                    _ => {
+                        i += 1;
+                        c.to_string()
+                    }
+                };
+                tokens.push(Token::Op(op));
+            }
+            '"' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        Some('"') if chars.get(i + 1) == Some(&'"') => {
+                            text.push('"');
+                            i += 2;
+                        }
+                        Some('"') => {
+                            i += 1;
+                            break;
+                        }
+                        Some(ch) => {
+                            text.push(*ch);
+                            i += 1;
+                        }
+                        None => return Err("字符串未闭合".to_string()),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            '\'' => {
+                let mut name = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        Some('\'') if chars.get(i + 1) == Some(&'\'') => {
+                            name.push('\'');
+                            i += 2;
+                        }
+                        Some('\'') => {
+                            i += 1;
+                            break;
+                        }
+                        Some(ch) => {
+                            name.push(*ch);
+                            i += 1;
+                        }
+                        None => return Err("工作表名称未闭合".to_string()),
+                    }
+                }
+                if chars.get(i) != Some(&'!') {
+                    return Err("工作表名称后缺少 !".to_string());
+                }
+                i += 1;
+                tokens.push(Token::Sheet(name));
+            }
+            _ if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                // 科学计数法
+                if i < chars.len() && (chars[i] == 'E' || chars[i] == 'e') {
+                    let mut j = i + 1;
+                    if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        i = j;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let text: String = chars[start..i].iter().collect();
+                tokens.push(Token::Number(text.parse().map_err(|_| format!("无效数字: {}", text))?));
+            }
+            _ if c.is_alphanumeric() || c == '_' || c == '$' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '$' | '.')) {
+                    i += 1;
+                }
+                let ident: String = chars[start..i].iter().collect();
+                if chars.get(i) == Some(&'!') {
+                    i += 1;
+                    tokens.push(Token::Sheet(ident));
+                } else {
+                    tokens.push(Token::Ident(ident));
+                }
+            }
+            _ => return Err(format!("无法识别的字符: {}", c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// 递归下降语法分析器
+/// 优先级从低到高：比较 < 连接 & < 加减 < 乘除 < 乘方 < 负号 < 百分号
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_op(&self, ops: &[&str]) -> Option<String> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(&op.as_str()) => Some(op.clone()),
+            _ => None,
+        }
+    }
+
+    fn binary_level(
+        &mut self,
+        ops: &[&str],
+        next: fn(&mut Self) -> Result<Expr, String>,
+    ) -> Result<Expr, String> {
+        let mut lhs = next(self)?;
+        while let Some(op) = self.peek_op(ops) {
+            self.pos += 1;
+            let rhs = next(self)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["=", "<>", "<", ">", "<=", ">="], Self::concat)
+    }
+
+    fn concat(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["&"], Self::additive)
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["+", "-"], Self::multiplicative)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["*", "/"], Self::power)
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["^"], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.peek_op(&["-", "+"]) {
+            Some(op) => {
+                self.pos += 1;
+                let inner = self.unary()?;
+                Ok(if op == "-" { Expr::Neg(Box::new(inner)) } else { inner })
+            }
+            None => self.postfix(),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        while self.peek_op(&["%"]).is_some() {
+            self.pos += 1;
+            expr = Expr::Percent(Box::new(expr));
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            // 整行引用（如 1:3）
+            Some(Token::Number(n)) if self.peek() == Some(&Token::Colon) => self.reference(None, &n.to_string()),
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Text(s)) => Ok(Expr::Text(s)),
+            Some(Token::LParen) => {
+                let expr = self.comparison()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("缺少右括号".to_string()),
+                }
+            }
+            Some(Token::Sheet(sheet)) => match self.next() {
+                Some(Token::Ident(reference)) => self.reference(Some(sheet), &reference),
+                Some(Token::Number(n)) => self.reference(Some(sheet), &n.to_string()),
+                _ => Err("工作表名称后缺少单元格引用".to_string()),
+            },
+            Some(Token::Ident(ident)) => {
+                if self.peek() == Some(&Token::LParen) {
+                    self.pos += 1;
+                    return self.call(ident.to_ascii_uppercase());
+                }
+                match ident.to_ascii_uppercase().as_str() {
+                    "TRUE" => Ok(Expr::Bool(true)),
+                    "FALSE" => Ok(Expr::Bool(false)),
+                    _ => self.reference(None, &ident),
+                }
+            }
+            token => Err(format!("意外的词法单元: {:?}", token)),
+        }
+    }
+
+    /// 解析单元格或区域引用，支持整列（A:C）和整行（1:3）引用
+    fn reference(&mut self, sheet: Option<String>, start: &str) -> Result<Expr, String> {
+        let start = start.replace('$', "");
+        let start_cell = parse_cell_ref(&start);
+
+        if self.peek() != Some(&Token::Colon) {
+            let (row, col) = start_cell.ok_or_else(|| format!("不支持的名称或引用: {}", start))?;
+            return Ok(Expr::Ref { sheet, row, col });
+        }
+
+        self.pos += 1;
+        let end = match self.next() {
+            Some(Token::Ident(end)) => end.replace('$', ""),
+            Some(Token::Number(n)) => n.to_string(),
+            _ => return Err("区域引用缺少结束单元格".to_string()),
+        };
+
+        if let Some(start) = start_cell {
+            let end = parse_cell_ref(&end).ok_or_else(|| format!("不支持的区域引用: {}", end))?;
+            return Ok(Expr::Range { sheet, start, end });
+        }
+
+        let (start, end) = match (parse_column(&start), parse_column(&end), parse_row(&start), parse_row(&end)) {
+            (Some(left), Some(right), _, _) => ((0, left), (LAST_ROW, right)),
+            (_, _, Some(top), Some(bottom)) => ((top, 0), (bottom, LAST_COL)),
+            _ => return Err(format!("不支持的区域引用: {}:{}", start, end)),
+        };
+        Ok(Expr::Range { sheet, start, end })
+    }
+
+    /// 解析函数参数列表
+    fn call(&mut self, name: String) -> Result<Expr, String> {
+        let mut args = Vec::new();
+
+        if self.peek() == Some(&Token::RParen) {
+            self.pos += 1;
+            return Ok(Expr::Call(name, args));
+        }
+
+        loop {
+            args.push(self.comparison()?);
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::RParen) => break,
+                _ => return Err(format!("函数 {} 参数列表未闭合", name)),
+            }
+        }
+
+        Ok(Expr::Call(name, args))
+    }
+}
+
+/// 整列引用的列号（如 `C` → 2）
+fn parse_column(text: &str) -> Option<u32> {
+    if text.is_empty() || text.len() > 3 || !text.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    parse_cell_ref(&format!("{}1", text)).map(|(_, col)| col).filter(|col| *col <= LAST_COL)
+}
+
+/// 整行引用的行号（如 `3` → 2）
+fn parse_row(text: &str) -> Option<u32> {
+    let row: u32 = text.parse().ok()?;
+    (1..=LAST_ROW + 1).contains(&row).then(|| row - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 由 (单元格, 内容) 列表构造单表工作簿，内容以 = 开头视为公式
+    fn book(cells: &[(String, String)]) -> FormulaBook {
+        let coords: Vec<(u32, u32)> = cells.iter().map(|(cell, _)| parse_cell_ref(cell).unwrap()).collect();
+        let end = coords.iter().fold((0, 0), |a, b| (a.0.max(b.0), a.1.max(b.1)));
+        let mut values = Range::new((0, 0), end);
+        let mut formulas = Range::new((0, 0), end);
+
+        for ((_, text), &pos) in cells.iter().zip(&coords) {
+            if let Some(formula) = text.strip_prefix('=') {
+                formulas.set_value(pos, formula.to_string());
+            } else if let Ok(n) = text.parse::<f64>() {
+                values.set_value(pos, Data::Float(n));
+            } else {
+                values.set_value(pos, Data::String(text.clone()));
+            }
+        }
+
+        let mut book = FormulaBook::new();
+        book.add_sheet("Sheet1", values, &formulas);
+        book
+    }
+
+    fn cells(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter().map(|(c, t)| (c.to_string(), t.to_string())).collect()
+    }
+
+    /// 求值并返回指定单元格的结果
+    fn eval(list: &[(String, String)], cell: &str) -> Data {
+        let pos = parse_cell_ref(cell).unwrap();
+        book(list).evaluate_missing()["Sheet1"][&pos].clone()
+    }
+
+    #[test]
+    fn operator_precedence() {
+        let list = cells(&[
+            ("A1", "=1+2*3^2"),
+            ("A2", "=-2^2"),
+            ("A3", "=(1+2)*3"),
+            ("A4", "=50%*4"),
+            ("A5", "=1+2=3"),
+            ("A6", "=\"a\"&1+1"),
+        ]);
+        assert_eq!(eval(&list, "A1"), Data::Float(19.0));
+        assert_eq!(eval(&list, "A2"), Data::Float(4.0));
+        assert_eq!(eval(&list, "A3"), Data::Float(9.0));
+        assert_eq!(eval(&list, "A4"), Data::Float(2.0));
+        assert_eq!(eval(&list, "A5"), Data::Bool(true));
+        assert_eq!(eval(&list, "A6"), Data::String("a2".to_string()));
+    }
+
+    #[test]
+    fn range_functions() {
+        let list = cells(&[
+            ("A1", "1"),
+            ("A2", "2"),
+            ("A3", "text"),
+            ("A4", "3"),
+            ("B1", "=SUM(A1:A4)"),
+            ("B2", "=AVERAGE(A1:A4)"),
+            ("B3", "=MAX(A:A)"),
+            ("B4", "=COUNT(1:2)"),
+            ("B5", "=SUM(A1:A4)/0"),
+        ]);
+        assert_eq!(eval(&list, "B1"), Data::Float(6.0));
+        assert_eq!(eval(&list, "B2"), Data::Float(2.0));
+        assert_eq!(eval(&list, "B3"), Data::Float(3.0));
+        assert_eq!(eval(&list, "B5"), Data::Error(CellErrorType::Div0));
+        // 1:2 包含 A1、A2 及 B1、B2 的结果
+        assert_eq!(eval(&list, "B4"), Data::Float(4.0));
+    }
+
+    #[test]
+    fn vlookup_exact_and_approximate() {
+        let list = cells(&[
+            ("A1", "Key"),
+            ("B1", "Value"),
+            ("A2", "10"),
+            ("B2", "a"),
+            ("A3", "20"),
+            ("B3", "b"),
+            ("A4", "30"),
+            ("B4", "c"),
+            ("D1", "=VLOOKUP(20,A1:B4,2,FALSE)"),
+            ("D2", "=VLOOKUP(25,A1:B4,2,FALSE)"),
+            ("D3", "=VLOOKUP(25,A1:B4,2,TRUE)"),
+            ("D4", "=VLOOKUP(99,A:B,2)"),
+            ("D5", "=VLOOKUP(5,A2:B4,2,TRUE)"),
+            ("D6", "=VLOOKUP(20,A1:B4,3,FALSE)"),
+        ]);
+        assert_eq!(eval(&list, "D1"), Data::String("b".to_string()));
+        assert_eq!(eval(&list, "D2"), Data::Error(CellErrorType::NA));
+        // 表头文本与查找值类型不同，近似匹配跳过该行
+        assert_eq!(eval(&list, "D3"), Data::String("b".to_string()));
+        assert_eq!(eval(&list, "D4"), Data::String("c".to_string()));
+        assert_eq!(eval(&list, "D5"), Data::Error(CellErrorType::NA));
+        assert_eq!(eval(&list, "D6"), Data::Error(CellErrorType::Ref));
+    }
+
+    #[test]
+    fn circular_references() {
+        let list = cells(&[
+            ("A1", "=B1+1"),
+            ("B1", "=A1+1"),
+            ("C1", "=C1"),
+            ("D1", "=SUM(D2:D3)"),
+            ("D2", "=D1"),
+            ("E1", "5"),
+            ("F1", "=E1*2"),
+        ]);
+        let results = book(&list).evaluate_missing();
+        let sheet = &results["Sheet1"];
+        assert_eq!(sheet[&(0, 0)], Data::Error(CellErrorType::Ref));
+        assert_eq!(sheet[&(0, 1)], Data::Error(CellErrorType::Ref));
+        assert_eq!(sheet[&(0, 2)], Data::Error(CellErrorType::Ref));
+        assert_eq!(sheet[&(0, 3)], Data::Error(CellErrorType::Ref));
+        assert_eq!(sheet[&(0, 5)], Data::Float(10.0));
+    }
+
+    #[test]
+    fn long_dependency_chain() {
+        let mut list = vec![("A1".to_string(), "1".to_string()), ("B1".to_string(), "=A1".to_string())];
+        for n in 2..=1000 {
+            list.push((format!("A{}", n), n.to_string()));
+            list.push((format!("B{}", n), format!("=B{}+A{}", n - 1, n)));
+        }
+        // 逆序排列，求值结果不应依赖公式的存储顺序
+        list.reverse();
+
+        let results = book(&list).evaluate_missing();
+        let sheet = &results["Sheet1"];
+        assert_eq!(sheet.len(), 1000);
+        assert_eq!(sheet[&(999, 1)], Data::Float(500_500.0));
+        assert_eq!(sheet[&(499, 1)], Data::Float(125_250.0));
+    }
+}
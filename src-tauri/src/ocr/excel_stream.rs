@@ -0,0 +1,250 @@
+// Excel 流式读取模块 - 逐行读取超大工作表并分批输出表格
+// xlsx/xlsb 使用 calamine 的单元格读取器，内存占用只与批大小有关，与工作表行数无关
+
+use calamine::{Data, Reader, Sheets};
+use crate::ocr::{CellValue, ExtractedTable, TableSource};
+use crate::ocr::excel::{ExcelOptions, ExcelProcessor};
+use crate::ocr::excel_format;
+
+/// 工作表尺寸声明的列数超过该值时视为不可信（如 `A1:XFD1048576`），改用数据实际的列范围；
+/// 数据实际的列范围也不超过该值，超出的列被丢弃
+const MAX_DIMENSION_COLS: u32 = 1024;
+
+/// Excel 流式处理器
+pub struct ExcelStreamer;
+
+/// 流式读取的结果
+#[derive(Debug, Default)]
+pub struct StreamSummary {
+    pub tables: usize,          // 输出的表格数
+    pub warnings: Vec<String>,  // 如超出列数上限被丢弃的列
+}
+
+/// 行批次缓冲区
+/// 按行接收单元格，攒满一批后交给回调输出
+struct RowBatcher<'a, F: FnMut(ExtractedTable) -> Result<(), String>> {
+    sheet: String,
+    batch_size: usize,
+    cols: Option<(u32, u32)>,  // 整个工作表的列范围，各批次共用
+    header: Option<Vec<(u32, Data)>>,  // 工作表的第一个非空行，重复放在后续批次的首行
+    current_row: Option<u32>,
+    current: Vec<(u32, Data)>,  // 当前行的 (列号, 值)
+    rows: Vec<(u32, Vec<(u32, Data)>)>,  // 当前批次的 (行号, 单元格)
+    emitted: usize,
+    truncated: bool,  // 是否有超出列数上限的单元格被丢弃
+    on_table: &'a mut F,
+}
+
+impl<'a, F: FnMut(ExtractedTable) -> Result<(), String>> RowBatcher<'a, F> {
+    /// # 参数
+    /// * `cols` - 工作表尺寸声明的列范围，缺失或不可信时为 `None`
+    fn new(sheet: &str, batch_size: usize, cols: Option<(u32, u32)>, on_table: &'a mut F) -> Self {
+        RowBatcher {
+            sheet: sheet.to_string(),
+            batch_size: batch_size.max(1),
+            cols: cols.filter(|(left, right)| left <= right && right - left < MAX_DIMENSION_COLS),
+            header: None,
+            current_row: None,
+            current: Vec::new(),
+            rows: Vec::new(),
+            emitted: 0,
+            truncated: false,
+            on_table,
+        }
+    }
+
+    /// 接收一个单元格（需按行优先顺序传入）
+    fn push(&mut self, row: u32, col: u32, value: Data) -> Result<(), String> {
+        if self.current_row != Some(row) {
+            self.finish_row()?;
+            self.current_row = Some(row);
+        }
+
+        if value != Data::Empty {
+            self.current.push((col, value));
+        }
+
+        Ok(())
+    }
+
+    /// 结束当前行，全空的行直接丢弃
+    fn finish_row(&mut self) -> Result<(), String> {
+        let Some(row) = self.current_row.take() else {
+            return Ok(());
+        };
+
+        let cells = std::mem::take(&mut self.current);
+        let blank = cells.iter().all(|(_, value)| match value {
+            Data::String(s) => s.trim().is_empty(),
+            _ => false,
+        });
+
+        if !blank {
+            self.rows.push((row, cells));
+        }
+
+        if self.rows.len() >= self.batch_size {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// 将当前批次转换为表格并输出
+    ///
+    /// 所有批次使用工作表的列范围，行内缺失的单元格补空；尺寸声明缺失或有数据超出时
+    /// 按已读到的数据扩展列范围，但不超过 `MAX_DIMENSION_COLS` 列。第一批以后的批次在首行重复工作表的表头行，
+    /// 来源区域只包含本批次的数据行
+    fn flush(&mut self) -> Result<(), String> {
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+
+        let mut batch = std::mem::take(&mut self.rows);
+
+        let cols = batch.iter().flat_map(|(_, cells)| cells.iter().map(|(c, _)| *c));
+        let (left, right) = cols.fold(self.cols, |bounds, col| match bounds {
+            Some((left, right)) => Some((left.min(col), right.max(col))),
+            None => Some((col, col)),
+        }).unwrap_or((0, 0));
+
+        // 个别远离数据区域的单元格（如 XFD 列）会使每行补齐上万个空单元格
+        let right = if right - left >= MAX_DIMENSION_COLS {
+            self.truncated = true;
+            left + MAX_DIMENSION_COLS - 1
+        } else {
+            right
+        };
+        self.cols = Some((left, right));
+        let width = (right - left + 1) as usize;
+
+        let top = batch.first().map(|(r, _)| *r).unwrap_or(0);
+        let bottom = batch.last().map(|(r, _)| *r).unwrap_or(0);
+
+        match &self.header {
+            Some(header) => batch.insert(0, (top, header.clone())),
+            None => self.header = batch.first().map(|(_, cells)| cells.clone()),
+        }
+
+        let mut rows = Vec::with_capacity(batch.len());
+        let mut raw_rows = Vec::with_capacity(batch.len());
+
+        for (_, cells) in batch {
+            let mut row = vec![String::new(); width];
+            let mut raw = vec![CellValue::Empty; width];
+
+            for (col, value) in cells.into_iter().filter(|(col, _)| *col <= right) {
+                let index = (col - left) as usize;
+                // 流式模式不加载数字格式表，日期仍由 calamine 按样式识别
+                row[index] = excel_format::format_cell(&value, None);
+                raw[index] = excel_format::raw_value(&value);
+            }
+
+            rows.push(row);
+            raw_rows.push(raw);
+        }
+
+        let table = ExtractedTable {
+            rows,
+            raw_rows: Some(raw_rows),
+            source: TableSource {
+                sheet: Some(self.sheet.clone()),
+                range: Some(excel_format::range_ref(top, left, bottom, right)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        (self.on_table)(table)?;
+        self.emitted += 1;
+
+        Ok(())
+    }
+
+    /// 输出剩余数据，返回输出的表格数和是否丢弃了超出列数上限的单元格
+    fn finish(mut self) -> Result<(usize, bool), String> {
+        self.finish_row()?;
+        self.flush()?;
+        Ok((self.emitted, self.truncated))
+    }
+}
+
+impl ExcelStreamer {
+    /// 流式读取工作簿，每个工作表按行分批输出为多个表格
+    ///
+    /// 与 `ExcelProcessor::extract_with_options` 相比，流式模式：
+    /// * 遵循工作表筛选和隐藏工作表选项
+    /// * 跳过全空的行，不处理合并单元格、公式求值、分块拆分和空行列裁剪
+    /// * 表格列范围取工作表尺寸（最多 `MAX_DIMENSION_COLS` 列），超过一批的工作表在每批首行重复表头行
+    /// * 不读取自定义数字格式（避免加载整张工作表的样式索引），数值按常规格式显示
+    ///
+    /// # 参数
+    /// * `file_path` - Excel 文件路径
+    /// * `options` - 导入选项
+    /// * `batch_size` - 每个表格的最大行数
+    /// * `on_table` - 每攒满一批时调用，返回错误时中止读取
+    ///
+    /// # 返回
+    /// 输出的表格数和警告
+    pub fn stream_tables<F>(
+        file_path: &str,
+        options: &ExcelOptions,
+        batch_size: usize,
+        mut on_table: F,
+    ) -> Result<StreamSummary, String>
+    where
+        F: FnMut(ExtractedTable) -> Result<(), String>,
+    {
+        let format = ExcelProcessor::detect_format(file_path)?;
+        let mut workbook = ExcelProcessor::open_workbook(file_path, format)?;
+        let selected = ExcelProcessor::select_sheets(&workbook, options)?;
+
+        let mut summary = StreamSummary::default();
+
+        for name in selected {
+            let (emitted, truncated) = match &mut workbook {
+                Sheets::Xlsx(xlsx) => {
+                    let mut cells = xlsx.worksheet_cells_reader(&name).map_err(|e| e.to_string())?;
+                    let dimensions = cells.dimensions();
+                    let mut batcher = RowBatcher::new(&name, batch_size, Some((dimensions.start.1, dimensions.end.1)), &mut on_table);
+                    while let Some(cell) = cells.next_cell().map_err(|e| e.to_string())? {
+                        let (row, col) = cell.get_position();
+                        batcher.push(row, col, Data::from(cell.get_value().clone()))?;
+                    }
+                    batcher.finish()?
+                }
+                Sheets::Xlsb(xlsb) => {
+                    let mut cells = xlsb.worksheet_cells_reader(&name).map_err(|e| e.to_string())?;
+                    let dimensions = cells.dimensions();
+                    let mut batcher = RowBatcher::new(&name, batch_size, Some((dimensions.start.1, dimensions.end.1)), &mut on_table);
+                    while let Some(cell) = cells.next_cell().map_err(|e| e.to_string())? {
+                        let (row, col) = cell.get_position();
+                        batcher.push(row, col, Data::from(cell.get_value().clone()))?;
+                    }
+                    batcher.finish()?
+                }
+                // xls/ods 没有单元格读取器，只能整表读入后分批输出
+                workbook => {
+                    let range = workbook.worksheet_range(&name).map_err(|e| e.to_string())?;
+                    let (start_row, start_col) = range.start().unwrap_or((0, 0));
+                    let cols = range.end().map(|(_, end_col)| (start_col, end_col));
+                    let mut batcher = RowBatcher::new(&name, batch_size, cols, &mut on_table);
+                    for (row, col, value) in range.used_cells() {
+                        batcher.push(start_row + row as u32, start_col + col as u32, value.clone())?;
+                    }
+                    batcher.finish()?
+                }
+            };
+
+            summary.tables += emitted;
+            if truncated {
+                summary.warnings.push(format!(
+                    "工作表 {} 的数据超过 {} 列，超出的列未导入",
+                    name, MAX_DIMENSION_COLS
+                ));
+            }
+        }
+
+        Ok(summary)
+    }
+}
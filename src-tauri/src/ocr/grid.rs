@@ -0,0 +1,95 @@
+// 表格网格模块 - 将带跨行/跨列信息的单元格排布为规则的二维表格
+// 用于 HTML、ODT、RTF 等以"逐行列出单元格 + 跨度"方式描述表格的格式
+
+use crate::ocr::MergedRange;
+
+/// 单个单元格的最大跨列数
+const MAX_COL_SPAN: usize = 1000;
+
+/// 表格的最大列数（与 Excel 工作表列数相同），超出部分的单元格丢弃
+const MAX_WIDTH: usize = 16384;
+
+/// 待排布的单元格
+#[derive(Debug, Clone)]
+pub struct GridCell {
+    pub text: String,      // 单元格文本
+    pub row_span: usize,   // 跨行数（至少为 1）
+    pub col_span: usize,   // 跨列数（至少为 1）
+}
+
+impl GridCell {
+    /// 创建不跨行列的单元格
+    pub fn new(text: String) -> Self {
+        GridCell { text, row_span: 1, col_span: 1 }
+    }
+}
+
+/// 将逐行列出的单元格排布为规则表格
+///
+/// 每个单元格放在当前行第一个未被上方跨行单元格占用的位置；
+/// 跨行数超出表格末尾时截断到最后一行，跨列数和表格宽度有上限，
+/// 避免异常的跨度值占用大量内存。合并区域会以左上角的值填充，
+/// 并记录为 `MergedRange`
+///
+/// # 返回
+/// 等宽的表格行和合并区域列表
+pub fn layout(rows: Vec<Vec<GridCell>>) -> (Vec<Vec<String>>, Vec<MergedRange>) {
+    let height = rows.len();
+    let mut grid: Vec<Vec<Option<String>>> = vec![Vec::new(); height];
+    let mut merges = Vec::new();
+
+    for (r, cells) in rows.into_iter().enumerate() {
+        let mut c = 0;
+
+        for cell in cells {
+            // 跳过被上方跨行单元格占用的位置
+            while grid[r].get(c).is_some_and(|slot| slot.is_some()) {
+                c += 1;
+            }
+
+            if c >= MAX_WIDTH {
+                break;
+            }
+
+            let row_span = cell.row_span.max(1).min(height - r);
+            let col_span = cell.col_span.clamp(1, MAX_COL_SPAN).min(MAX_WIDTH - c);
+
+            for row in grid.iter_mut().skip(r).take(row_span) {
+                if row.len() < c + col_span {
+                    row.resize(c + col_span, None);
+                }
+                for slot in &mut row[c..c + col_span] {
+                    *slot = Some(cell.text.clone());
+                }
+            }
+
+            if row_span > 1 || col_span > 1 {
+                merges.push(MergedRange { row: r, col: c, row_span, col_span });
+            }
+
+            c += col_span;
+        }
+    }
+
+    let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    let rows = grid
+        .into_iter()
+        .map(|row| {
+            let mut row: Vec<String> = row.into_iter().map(Option::unwrap_or_default).collect();
+            row.resize(width, String::new());
+            row
+        })
+        .collect();
+
+    (rows, merges)
+}
+
+/// 整理单元格文本：每行内的连续空白合并为一个空格，去掉空行
+pub fn normalize_text(text: &str) -> String {
+    text.split('\n')
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,443 @@
+// HTML 解析模块 - 提取网页和 MHTML 存档中的 <table> 表格
+// 容错解析（不要求标签闭合），支持 rowspan/colspan、嵌套表格和表格标题
+
+use crate::ocr::grid::{self, normalize_text, GridCell};
+use crate::ocr::mime::{self, MimePart};
+use crate::ocr::{encoding, ExtractedTable, TableSource};
+use regex::Regex;
+
+/// HTML 处理器
+pub struct HtmlProcessor;
+
+/// colspan 上限（与浏览器一致）
+const MAX_COL_SPAN: usize = 1000;
+
+/// rowspan 上限（与浏览器一致），rowspan="0" 表示延伸到表格末尾
+const MAX_ROW_SPAN: usize = 65534;
+
+/// 单元格内会产生换行的块级元素
+const BLOCK_TAGS: &[&str] = &["p", "div", "li", "ul", "ol", "h1", "h2", "h3", "h4", "h5", "h6"];
+
+/// HTML 词法单元
+#[derive(Debug)]
+enum Token {
+    Start { name: String, attrs: Vec<(String, String)> },
+    End { name: String },
+    Text(String),
+}
+
+/// 正在构建的表格
+#[derive(Default)]
+struct TableBuilder {
+    order: usize,                 // 开始标签出现的顺序，用于按文档顺序输出
+    caption: String,
+    in_caption: bool,
+    rows: Vec<Vec<GridCell>>,
+    row_open: bool,
+    cell: Option<GridCell>,
+}
+
+impl TableBuilder {
+    /// 结束当前单元格
+    fn close_cell(&mut self) {
+        if let Some(mut cell) = self.cell.take() {
+            cell.text = normalize_text(&cell.text);
+            if !self.row_open {
+                self.rows.push(Vec::new());
+                self.row_open = true;
+            }
+            if let Some(row) = self.rows.last_mut() {
+                row.push(cell);
+            }
+        }
+    }
+
+    /// 结束当前行
+    fn close_row(&mut self) {
+        self.close_cell();
+        self.row_open = false;
+    }
+
+    /// 追加文本到单元格或标题
+    fn push_text(&mut self, text: &str) {
+        if let Some(cell) = &mut self.cell {
+            cell.text.push_str(text);
+        } else if self.in_caption {
+            self.caption.push_str(text);
+        }
+    }
+
+    /// 转换为表格
+    fn finish(mut self) -> ExtractedTable {
+        self.close_row();
+        let (rows, merges) = grid::layout(self.rows);
+        let caption = normalize_text(&self.caption);
+
+        ExtractedTable {
+            rows,
+            merges,
+            source: TableSource {
+                caption: (!caption.is_empty()).then_some(caption),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+impl HtmlProcessor {
+    /// 从 HTML 或 MHTML 文件提取所有表格
+    ///
+    /// # 参数
+    /// * `file_path` - 文件路径（.html/.htm/.mht/.mhtml）
+    ///
+    /// # 返回
+    /// 按文档顺序排列的表格列表（嵌套表格单独输出），表格标题记录在 `source.caption`
+    pub fn extract_tables(file_path: &str) -> Result<Vec<ExtractedTable>, String> {
+        let bytes = std::fs::read(file_path).map_err(|e| e.to_string())?;
+
+        if !mime::looks_like_mime(&bytes) {
+            return Ok(Self::parse_tables(&Self::decode_html(&bytes)));
+        }
+
+        // MHTML：依次解析存档中的所有 HTML 段
+        let message = MimePart::parse(&bytes);
+        let tables = message
+            .leaves()
+            .into_iter()
+            .filter(|part| part.mime_type() == "text/html")
+            .flat_map(|part| {
+                let html = match part.param("charset") {
+                    Some(_) => part.text(),
+                    None => Self::decode_html(&part.body),
+                };
+                Self::parse_tables(&html)
+            })
+            .collect();
+
+        Ok(tables)
+    }
+
+    /// 解码 HTML 字节，优先使用 BOM，其次使用 `<meta>` 声明的字符集
+    pub fn decode_html(bytes: &[u8]) -> String {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(4096)]);
+        let charset = Regex::new(r#"(?i)<meta[^>]+charset\s*=\s*["']?([\w.:-]+)"#)
+            .ok()
+            .and_then(|re| re.captures(&head).map(|caps| caps[1].to_string()));
+
+        match charset {
+            Some(label) => encoding::decode_with_label(bytes, &label),
+            None => encoding::decode_text(bytes).0,
+        }
+    }
+
+    /// 解析 HTML 文本中的所有表格
+    pub fn parse_tables(html: &str) -> Vec<ExtractedTable> {
+        let mut stack: Vec<TableBuilder> = Vec::new();
+        let mut done: Vec<(usize, ExtractedTable)> = Vec::new();
+        let mut order = 0;
+
+        for token in Self::tokenize(html) {
+            match token {
+                Token::Start { name, attrs } => match name.as_str() {
+                    "table" => {
+                        stack.push(TableBuilder { order, ..Default::default() });
+                        order += 1;
+                    }
+                    _ => {
+                        let Some(table) = stack.last_mut() else { continue };
+                        match name.as_str() {
+                            "caption" => {
+                                table.close_row();
+                                table.in_caption = true;
+                            }
+                            "tr" => {
+                                table.close_row();
+                                table.rows.push(Vec::new());
+                                table.row_open = true;
+                            }
+                            "td" | "th" => {
+                                table.close_cell();
+                                let span = |key: &str| {
+                                    attrs
+                                        .iter()
+                                        .find(|(k, _)| k == key)
+                                        .and_then(|(_, v)| v.trim().parse::<usize>().ok())
+                                };
+                                let row_span = match span("rowspan") {
+                                    Some(0) => MAX_ROW_SPAN,
+                                    Some(n) => n.min(MAX_ROW_SPAN),
+                                    None => 1,
+                                };
+                                let col_span = span("colspan").unwrap_or(1).clamp(1, MAX_COL_SPAN);
+                                table.cell = Some(GridCell { text: String::new(), row_span, col_span });
+                            }
+                            "thead" | "tbody" | "tfoot" => table.close_row(),
+                            "br" => table.push_text("\n"),
+                            tag if BLOCK_TAGS.contains(&tag) => table.push_text("\n"),
+                            _ => {}
+                        }
+                    }
+                },
+                Token::End { name } => {
+                    if name == "table" {
+                        if let Some(table) = stack.pop() {
+                            let order = table.order;
+                            let table = table.finish();
+                            if !table.rows.is_empty() {
+                                done.push((order, table));
+                            }
+                        }
+                        continue;
+                    }
+
+                    let Some(table) = stack.last_mut() else { continue };
+                    match name.as_str() {
+                        "caption" => table.in_caption = false,
+                        "tr" | "thead" | "tbody" | "tfoot" => table.close_row(),
+                        "td" | "th" => table.close_cell(),
+                        tag if BLOCK_TAGS.contains(&tag) => table.push_text("\n"),
+                        _ => {}
+                    }
+                }
+                Token::Text(text) => {
+                    if let Some(table) = stack.last_mut() {
+                        table.push_text(&text);
+                    }
+                }
+            }
+        }
+
+        // 未闭合的表格按出现顺序补齐
+        while let Some(table) = stack.pop() {
+            let order = table.order;
+            let table = table.finish();
+            if !table.rows.is_empty() {
+                done.push((order, table));
+            }
+        }
+
+        done.sort_by_key(|(order, _)| *order);
+        done.into_iter().map(|(_, table)| table).collect()
+    }
+
+    /// 将 HTML 拆分为标签和文本
+    /// 跳过注释、声明和 script/style 内容，文本中的空白统一为空格
+    fn tokenize(html: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut rest = html;
+
+        while !rest.is_empty() {
+            let Some(lt) = rest.find('<') else {
+                tokens.push(Token::Text(decode_entities(rest)));
+                break;
+            };
+
+            if lt > 0 {
+                tokens.push(Token::Text(decode_entities(&rest[..lt])));
+            }
+            rest = &rest[lt..];
+
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                rest = comment.find("-->").map(|i| &comment[i + 3..]).unwrap_or("");
+                continue;
+            }
+
+            if rest.starts_with("<!") || rest.starts_with("<?") {
+                rest = rest.find('>').map(|i| &rest[i + 1..]).unwrap_or("");
+                continue;
+            }
+
+            let is_end = rest.starts_with("</");
+            let name_start = if is_end { 2 } else { 1 };
+            let name_len = rest[name_start..]
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len() - name_start);
+
+            // "<" 后不是标签名时按普通文本处理
+            if name_len == 0 {
+                tokens.push(Token::Text("<".to_string()));
+                rest = &rest[1..];
+                continue;
+            }
+
+            let name = rest[name_start..name_start + name_len].to_ascii_lowercase();
+            // 文件截断时没有 `>`，属性取到末尾
+            let (body_end, tag_end) = match Self::find_tag_end(rest) {
+                Some(end) => (end - 1, end),
+                None => (rest.len(), rest.len()),
+            };
+            let body = &rest[name_start + name_len..body_end.max(name_start + name_len)];
+            rest = &rest[tag_end..];
+
+            if is_end {
+                tokens.push(Token::End { name });
+                continue;
+            }
+
+            let attrs = Self::parse_attrs(body);
+            tokens.push(Token::Start { name: name.clone(), attrs });
+
+            // script/style 内容不是文本
+            if name == "script" || name == "style" {
+                let close = format!("</{}", name);
+                let lower = rest.to_ascii_lowercase();
+                rest = lower.find(&close).map(|i| &rest[i..]).unwrap_or("");
+            }
+        }
+
+        tokens
+    }
+
+    /// 查找标签结束位置（`>` 之后），忽略引号内的 `>`
+    fn find_tag_end(tag: &str) -> Option<usize> {
+        let mut quote: Option<char> = None;
+
+        for (i, c) in tag.char_indices() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (None, '>') => return Some(i + 1),
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    /// 解析标签属性，属性名转为小写
+    fn parse_attrs(body: &str) -> Vec<(String, String)> {
+        let mut attrs = Vec::new();
+        let mut chars = body.trim_end_matches('/').chars().peekable();
+
+        loop {
+            while chars.peek().is_some_and(|c| c.is_whitespace() || *c == '/') {
+                chars.next();
+            }
+
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '=' {
+                    break;
+                }
+                name.push(c.to_ascii_lowercase());
+                chars.next();
+            }
+            if name.is_empty() {
+                break;
+            }
+
+            while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                chars.next();
+            }
+
+            let mut value = String::new();
+            if chars.peek() == Some(&'=') {
+                chars.next();
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                match chars.peek().copied() {
+                    Some(q) if q == '"' || q == '\'' => {
+                        chars.next();
+                        for c in chars.by_ref() {
+                            if c == q {
+                                break;
+                            }
+                            value.push(c);
+                        }
+                    }
+                    _ => {
+                        while let Some(&c) = chars.peek() {
+                            if c.is_whitespace() {
+                                break;
+                            }
+                            value.push(c);
+                            chars.next();
+                        }
+                    }
+                }
+            }
+
+            attrs.push((name, decode_entities(&value)));
+        }
+
+        attrs
+    }
+}
+
+/// 解码 HTML 字符实体，并将源码中的换行等空白统一为空格
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        push_text(&mut out, &rest[..amp]);
+        rest = &rest[amp..];
+
+        let end = rest[1..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '#'))
+            .map(|i| i + 1)
+            .unwrap_or(rest.len());
+        let entity = &rest[1..end];
+
+        match entity_char(entity) {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end..];
+                rest = rest.strip_prefix(';').unwrap_or(rest);
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    push_text(&mut out, rest);
+    out
+}
+
+/// 追加文本，空白字符统一为空格
+fn push_text(out: &mut String, text: &str) {
+    out.extend(text.chars().map(|c| if c.is_whitespace() { ' ' } else { c }));
+}
+
+/// 字符实体对应的字符（不含 `&` 和 `;`）
+fn entity_char(entity: &str) -> Option<char> {
+    if let Some(num) = entity.strip_prefix('#') {
+        let code = match num.strip_prefix('x').or_else(|| num.strip_prefix('X')) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    let c = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" | "ensp" | "emsp" | "thinsp" => ' ',
+        "copy" => '©',
+        "reg" => '®',
+        "times" => '×',
+        "divide" => '÷',
+        "middot" => '·',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "yen" => '¥',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "deg" => '°',
+        "plusmn" => '±',
+        _ => return None,
+    };
+
+    Some(c)
+}
@@ -0,0 +1,436 @@
+// MIME 解析模块 - 解析 MHTML 网页存档、.eml 邮件等 MIME 格式文本
+// 支持多段（multipart）嵌套、base64 和 quoted-printable 传输编码、RFC 2047/2231 编码的头部和文件名
+
+use crate::ocr::encoding;
+
+/// multipart 最多嵌套的层数，更深的段不再拆分，按单个段保留原始正文
+const MAX_DEPTH: usize = 32;
+
+/// MIME 段
+#[derive(Debug, Clone, Default)]
+pub struct MimePart {
+    pub headers: Vec<(String, String)>,  // 头部字段（名称, 值），按出现顺序
+    pub body: Vec<u8>,                   // 已解除传输编码的正文
+    pub parts: Vec<MimePart>,            // multipart 的子段
+}
+
+impl MimePart {
+    /// 解析 MIME 消息
+    pub fn parse(data: &[u8]) -> MimePart {
+        Self::parse_nested(data, 0)
+    }
+
+    /// 解析第 `depth` 层的 MIME 段
+    fn parse_nested(data: &[u8], depth: usize) -> MimePart {
+        let (header_block, body) = split_header(data);
+        // 部分邮件客户端直接在头部写入 GBK 等 8 位编码的文件名
+        let headers = parse_headers(&encoding::decode_text(header_block).0);
+
+        let mut part = MimePart { headers, body: Vec::new(), parts: Vec::new() };
+
+        if part.mime_type().starts_with("multipart/") && depth < MAX_DEPTH {
+            if let Some(boundary) = part.param("boundary") {
+                part.parts = split_multipart(body, &boundary)
+                    .into_iter()
+                    .map(|data| Self::parse_nested(data, depth + 1))
+                    .collect();
+                return part;
+            }
+        }
+
+        part.body = match part.header("content-transfer-encoding").map(|v| v.to_ascii_lowercase()) {
+            Some(e) if e.trim() == "base64" => decode_base64(body),
+            Some(e) if e.trim() == "quoted-printable" => decode_quoted_printable(body),
+            _ => body.to_vec(),
+        };
+
+        part
+    }
+
+    /// 获取头部字段值（名称不区分大小写，取第一个）
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// 内容类型（小写，不含参数），缺省为 text/plain
+    pub fn mime_type(&self) -> String {
+        self.header("content-type")
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "text/plain".to_string())
+    }
+
+    /// 获取 Content-Type 参数（如 charset、boundary）
+    pub fn param(&self, name: &str) -> Option<String> {
+        header_param(self.header("content-type")?, name)
+    }
+
+    /// 按 charset 参数解码正文文本
+    pub fn text(&self) -> String {
+        match self.param("charset") {
+            Some(charset) => encoding::decode_with_label(&self.body, &charset),
+            None => encoding::decode_text(&self.body).0,
+        }
+    }
+
+    /// 附件文件名：优先 Content-Disposition 的 filename，其次 Content-Type 的 name
+    pub fn filename(&self) -> Option<String> {
+        self.header("content-disposition")
+            .and_then(|v| header_param(v, "filename"))
+            .or_else(|| self.param("name"))
+            .map(|name| decode_header(&name))
+            .filter(|name| !name.trim().is_empty())
+    }
+
+    /// 是否为附件：声明为 attachment，或带文件名的非正文段
+    pub fn is_attachment(&self) -> bool {
+        let disposition = self
+            .header("content-disposition")
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase());
+
+        match disposition.as_deref() {
+            Some("attachment") => true,
+            _ => self.filename().is_some() && !self.mime_type().starts_with("text/"),
+        }
+    }
+
+    /// 深度优先列出所有非 multipart 的叶子段
+    pub fn leaves(&self) -> Vec<&MimePart> {
+        if self.parts.is_empty() {
+            return vec![self];
+        }
+        self.parts.iter().flat_map(|part| part.leaves()).collect()
+    }
+}
+
+/// 是否看起来是 MIME 消息（以头部字段开头且声明了 MIME 版本或内容类型）
+pub fn looks_like_mime(data: &[u8]) -> bool {
+    let (header_block, _) = split_header(data);
+    let headers = parse_headers(&String::from_utf8_lossy(header_block));
+    headers
+        .iter()
+        .any(|(key, _)| key.eq_ignore_ascii_case("mime-version") || key.eq_ignore_ascii_case("content-type"))
+}
+
+/// 获取头部字段中的参数值，如 `text/html; charset="gbk"` 中的 charset
+/// 支持 RFC 2231 的扩展参数（`filename*=UTF-8''%E8%A1%A8.xlsx`）和分段参数（`filename*0*=`）
+pub fn header_param(value: &str, name: &str) -> Option<String> {
+    let params = parse_params(value);
+    let name = name.to_ascii_lowercase();
+
+    if let Some((_, v)) = params.iter().find(|(k, _)| *k == format!("{}*", name)) {
+        return Some(decode_extended(v));
+    }
+
+    // 分段参数按序号拼接，带 * 的分段需要百分号解码，字符集由第一段声明
+    let mut pieces: Vec<(usize, bool, &str)> = params
+        .iter()
+        .filter_map(|(k, v)| {
+            let rest = k.strip_prefix(&name)?.strip_prefix('*')?;
+            let (index, encoded) = match rest.strip_suffix('*') {
+                Some(index) => (index, true),
+                None => (rest, false),
+            };
+            Some((index.parse().ok()?, encoded, v.as_str()))
+        })
+        .collect();
+
+    if !pieces.is_empty() {
+        pieces.sort_by_key(|(index, _, _)| *index);
+        let mut charset = None;
+        let mut bytes = Vec::new();
+        for (_, encoded, piece) in pieces {
+            if encoded {
+                let (raw, declared) = decode_extended_bytes(piece, charset.is_none());
+                charset = charset.or(declared);
+                bytes.extend(raw);
+            } else {
+                bytes.extend(piece.as_bytes());
+            }
+        }
+        return Some(match charset {
+            Some(charset) => encoding::decode_with_label(&bytes, &charset),
+            None => encoding::decode_text(&bytes).0,
+        });
+    }
+
+    params.into_iter().find(|(k, _)| *k == name).map(|(_, v)| v)
+}
+
+/// 拆分头部参数（引号内的分号不作为分隔符），参数名转为小写
+fn parse_params(value: &str) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in value.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            ';' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    fields.push(current);
+
+    for field in fields.into_iter().skip(1) {
+        if let Some((key, val)) = field.split_once('=') {
+            let val = val.trim();
+            let val = val.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(val);
+            params.push((key.trim().to_ascii_lowercase(), val.replace("\\\"", "\"")));
+        }
+    }
+
+    params
+}
+
+/// 解码 RFC 2231 扩展参数值（`charset'language'%XX...`）
+fn decode_extended(value: &str) -> String {
+    let (bytes, charset) = decode_extended_bytes(value, true);
+    match charset {
+        Some(charset) => encoding::decode_with_label(&bytes, &charset),
+        None => encoding::decode_text(&bytes).0,
+    }
+}
+
+/// 百分号解码扩展参数值，`with_charset` 为真时先剥离 `charset'language'` 前缀
+fn decode_extended_bytes(value: &str, with_charset: bool) -> (Vec<u8>, Option<String>) {
+    let mut value = value;
+    let mut charset = None;
+
+    if with_charset {
+        let mut parts = value.splitn(3, '\'');
+        if let (Some(cs), Some(_), Some(rest)) = (parts.next(), parts.next(), parts.next()) {
+            if !cs.is_empty() {
+                charset = Some(cs.to_string());
+            }
+            value = rest;
+        }
+    }
+
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    (out, charset)
+}
+
+/// 解码 RFC 2047 编码字（`=?UTF-8?B?...?=`、`=?GBK?Q?...?=`），相邻编码字之间的空白会被忽略
+pub fn decode_header(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut last_was_encoded = false;
+
+    while let Some(start) = rest.find("=?") {
+        let (before, candidate) = rest.split_at(start);
+
+        let decoded = decode_encoded_word(candidate);
+        let Some((text, consumed)) = decoded else {
+            out.push_str(before);
+            out.push_str("=?");
+            rest = &candidate[2..];
+            last_was_encoded = false;
+            continue;
+        };
+
+        if !(last_was_encoded && before.trim().is_empty()) {
+            out.push_str(before);
+        }
+        out.push_str(&text);
+        rest = &candidate[consumed..];
+        last_was_encoded = true;
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// 解码单个编码字，返回解码结果和消耗的字节数
+fn decode_encoded_word(word: &str) -> Option<(String, usize)> {
+    let body = word.strip_prefix("=?")?;
+    let (charset, body) = body.split_once('?')?;
+    let (kind, body) = body.split_once('?')?;
+    let end = body.find("?=")?;
+    let payload = &body[..end];
+
+    let bytes = match kind {
+        "B" | "b" => decode_base64(payload.as_bytes()),
+        "Q" | "q" => decode_quoted_printable(payload.replace('_', " ").as_bytes()),
+        _ => return None,
+    };
+
+    // body 是 word 的后缀，据此计算编码字结束位置
+    let consumed = word.len() - body.len() + end + 2;
+    // 字符集可能带语言后缀，如 UTF-8*zh
+    let charset = charset.split('*').next().unwrap_or(charset);
+
+    Some((encoding::decode_with_label(&bytes, charset), consumed))
+}
+
+/// 在第一个空行处分割头部和正文
+fn split_header(data: &[u8]) -> (&[u8], &[u8]) {
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let line_end = data[pos..].iter().position(|b| *b == b'\n').map(|i| pos + i);
+        let Some(line_end) = line_end else { break };
+
+        let line = &data[pos..line_end];
+        if line.is_empty() || line == b"\r" {
+            return (&data[..pos], &data[line_end + 1..]);
+        }
+        pos = line_end + 1;
+    }
+
+    (data, &[])
+}
+
+/// 解析头部字段，处理折行（以空白开头的行接续上一字段）
+fn parse_headers(block: &str) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+
+    for line in block.lines() {
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once(':') {
+            headers.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    headers
+}
+
+/// 按分隔符拆分 multipart 正文，忽略首段前言和结束标记后的内容
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut pos = 0;
+
+    while pos < body.len() {
+        let line_end = body[pos..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map(|i| pos + i + 1)
+            .unwrap_or(body.len());
+        let line = trim_line_end(&body[pos..line_end]);
+
+        if line.starts_with(delimiter) {
+            if let Some(s) = start {
+                // 分隔符前的换行属于分隔符
+                parts.push(trim_line_end(&body[s..pos]));
+            }
+            if line[delimiter.len()..].starts_with(b"--") {
+                return parts;
+            }
+            start = Some(line_end);
+        }
+
+        pos = line_end;
+    }
+
+    if let Some(s) = start {
+        parts.push(&body[s.min(body.len())..]);
+    }
+
+    parts
+}
+
+/// 去掉行尾的 `\r\n` 或 `\n`
+fn trim_line_end(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// 解码 base64（忽略换行等非编码字符）
+pub fn decode_base64(data: &[u8]) -> Vec<u8> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+            b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+            b'+' | b'-' => Some(62),
+            b'/' | b'_' => Some(63),
+            _ => None,
+        }
+    }
+
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for &c in data {
+        if c == b'=' {
+            break;
+        }
+        let Some(v) = value(c) else { continue };
+        buffer = (buffer << 6) | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    out
+}
+
+/// 解码 quoted-printable（`=XX` 转义和 `=` 软换行）
+pub fn decode_quoted_printable(data: &[u8]) -> Vec<u8> {
+    let hex = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+
+    while i < data.len() {
+        if data[i] != b'=' {
+            out.push(data[i]);
+            i += 1;
+            continue;
+        }
+
+        match (data.get(i + 1).copied(), data.get(i + 2).copied()) {
+            (Some(b'\r'), Some(b'\n')) => i += 3,
+            (Some(b'\n'), _) => i += 2,
+            (Some(a), Some(b)) if hex(a).is_some() && hex(b).is_some() => {
+                out.push(hex(a).unwrap_or(0) << 4 | hex(b).unwrap_or(0));
+                i += 3;
+            }
+            _ => {
+                out.push(b'=');
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
@@ -1,15 +1,144 @@
 // OCR 模块 - 公共定义和类型导出
-// 提供 OCR、PDF、Word、Excel 解析功能的统一接口
+// 提供 OCR、PDF、Word、Excel、CSV、HTML、ODT、RTF、PPTX、邮件解析功能的统一接口
 
 pub mod ocr;
 pub mod pdf;
 pub mod word;
 pub mod excel;
+pub mod excel_format;
+pub mod excel_formula;
+pub mod excel_stream;
+pub mod csv;
+pub mod html;
+pub mod encoding;
+pub mod mime;
+pub mod grid;
+pub mod odt;
+pub mod rtf;
+pub mod pptx;
+pub mod cfb;
+pub mod email;
+pub mod archive;
+pub mod detect;
+
+use serde::Serialize;
 
 // 表格提取结果结构体
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ExtractedTable {
-    pub rows: Vec<Vec<String>>,  // 表格数据，二维字符串数组
+    pub rows: Vec<Vec<String>>,  // 表格数据，二维字符串数组（显示文本）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_rows: Option<Vec<Vec<CellValue>>>,  // 保留类型的原始值（仅电子表格来源）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub merges: Vec<MergedRange>,  // 合并单元格区域（相对表格左上角）
+    #[serde(skip_serializing_if = "TableSource::is_empty")]
+    pub source: TableSource,  // 表格在源文件中的位置
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub formulas: Vec<FormulaCell>,  // 公式单元格（电子表格）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,  // 识别置信度 0.0 - 100.0（仅 OCR 来源）
+}
+
+impl ExtractedTable {
+    /// 识别表头：第一行至少两列、全部非空、互不重复且不是数字时视为表头
+    pub fn detect_headers(&self) -> Option<Vec<String>> {
+        let first = self.rows.first()?;
+        let cells: Vec<String> = first.iter().map(|c| c.trim().to_string()).collect();
+
+        if cells.len() < 2 || self.rows.len() < 2 {
+            return None;
+        }
+        let is_label = |c: &String| !c.is_empty() && c.replace([',', '%'], "").parse::<f64>().is_err();
+        let unique = cells.iter().collect::<std::collections::HashSet<_>>().len() == cells.len();
+
+        (unique && cells.iter().all(is_label)).then_some(cells)
+    }
+}
+
+// 公式单元格
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormulaCell {
+    pub row: usize,        // 行（相对表格左上角，从 0 开始）
+    pub col: usize,        // 列（相对表格左上角，从 0 开始）
+    pub formula: String,   // 公式文本，含前导 =
+    pub evaluated: bool,   // 单元格值是否由导入时求值得到（文件中缺少缓存值）
+}
+
+// 文档内容（表格和正文），用于 ODT、RTF 等同时包含表格和段落的文档
+#[derive(Debug, Default)]
+pub struct DocumentContent {
+    pub tables: Vec<ExtractedTable>,  // 按文档顺序排列的表格
+    pub text: String,                 // 表格以外的正文，每段一行
+}
+
+impl DocumentContent {
+    /// 转换为表格列表，正文不为空时追加一个单列表格（每段一行）
+    pub fn into_tables(self) -> Vec<ExtractedTable> {
+        let mut tables = self.tables;
+
+        let lines: Vec<Vec<String>> = self
+            .text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| vec![line.trim().to_string()])
+            .collect();
+
+        if !lines.is_empty() {
+            tables.push(ExtractedTable { rows: lines, ..Default::default() });
+        }
+
+        tables
+    }
+}
+
+// 表格来源位置
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,  // 工作表名称（电子表格）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<String>,  // 单元格区域，如 `B3:H20`（电子表格）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,  // 表格标题（HTML <caption> 等）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slide: Option<u32>,  // 幻灯片编号，从 1 开始（演示文稿）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slide_title: Option<String>,  // 幻灯片标题（演示文稿）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,  // 起始页码，从 1 开始（PDF）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_page: Option<u32>,  // 跨页时的结束页码（PDF）
+}
+
+impl TableSource {
+    /// 是否没有任何位置信息
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// 合并单元格区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MergedRange {
+    pub row: usize,       // 起始行（从 0 开始）
+    pub col: usize,       // 起始列（从 0 开始）
+    pub row_span: usize,  // 跨行数
+    pub col_span: usize,  // 跨列数
+}
+
+// 带类型的单元格原始值
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum CellValue {
+    Empty,
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    DateTime(String),   // ISO 8601 日期时间
+    Duration(String),   // 耗时，[h]:mm:ss
+    Error(String),
 }
 
 // 页面文本结构体（用于 PDF）
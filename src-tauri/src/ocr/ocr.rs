@@ -73,7 +73,11 @@ pub fn ocr_result_to_table(result: OcrResult) -> ExtractedTable {
         .map(|line| vec![line.trim().to_string()])
         .collect();
 
-    ExtractedTable { rows: lines }
+    ExtractedTable {
+        rows: lines,
+        confidence: Some(result.confidence),
+        ..Default::default()
+    }
 }
 
 /// 从图片中检测表格区域（简单实现）
@@ -0,0 +1,242 @@
+// ODT 解析模块 - 读取 OpenDocument 文本文档（WPS / LibreOffice）
+// 解析 content.xml 中的表格（含合并单元格和嵌套表格）和正文段落
+
+use std::io::Read;
+use quick_xml::events::{BytesStart, Event};
+use crate::ocr::grid::{self, normalize_text, GridCell};
+use crate::ocr::{DocumentContent, ExtractedTable, MergedRange};
+
+/// ODT 文档处理器
+pub struct OdtProcessor;
+
+/// 重复行/列的展开上限，避免空白区域的超大重复次数撑爆内存
+const MAX_REPEAT: usize = 1000;
+
+/// 正在构建的表格
+#[derive(Default)]
+struct TableBuilder {
+    order: usize,
+    rows: Vec<Vec<GridCell>>,
+    row_repeat: usize,
+    cell: Option<GridCell>,
+    cell_repeat: usize,
+}
+
+impl OdtProcessor {
+    /// 从 ODT 文件提取表格
+    ///
+    /// # 参数
+    /// * `file_path` - ODT 文件路径
+    ///
+    /// # 返回
+    /// 表格列表，正文段落不为空时额外追加一个单列表格（每段一行）
+    pub fn extract_tables(file_path: &str) -> Result<Vec<ExtractedTable>, String> {
+        Ok(Self::parse_file(file_path)?.into_tables())
+    }
+
+    /// 解析 ODT 文件
+    pub fn parse_file(file_path: &str) -> Result<DocumentContent, String> {
+        let file = std::fs::File::open(file_path).map_err(|e| e.to_string())?;
+        let mut archive = zip::ZipArchive::new(std::io::BufReader::new(file)).map_err(|e| e.to_string())?;
+
+        let mut xml = Vec::new();
+        archive
+            .by_name("content.xml")
+            .map_err(|_| "ODT 文件中未找到 content.xml".to_string())?
+            .read_to_end(&mut xml)
+            .map_err(|e| e.to_string())?;
+
+        Self::parse_content(&xml)
+    }
+
+    /// 解析 content.xml
+    pub fn parse_content(xml: &[u8]) -> Result<DocumentContent, String> {
+        let mut reader = quick_xml::Reader::from_reader(xml);
+        reader.trim_text(false);
+
+        let mut stack: Vec<TableBuilder> = Vec::new();
+        let mut done: Vec<(usize, ExtractedTable)> = Vec::new();
+        let mut order = 0;
+        let mut text = String::new();
+        // 批注等不属于正文的子树深度
+        let mut skip_depth = 0usize;
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            let event = reader.read_event_into(&mut buf).map_err(|e| e.to_string())?;
+
+            if skip_depth > 0 {
+                match event {
+                    Event::Start(_) => skip_depth += 1,
+                    Event::End(_) => skip_depth -= 1,
+                    Event::Eof => break,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match event {
+                Event::Start(e) => match e.local_name().as_ref() {
+                    b"annotation" | b"note-citation" | b"tracked-changes" => skip_depth = 1,
+                    b"table" => {
+                        stack.push(TableBuilder { order, ..Default::default() });
+                        order += 1;
+                    }
+                    b"table-row" => {
+                        if let Some(table) = stack.last_mut() {
+                            table.rows.push(Vec::new());
+                            table.row_repeat = repeat(&e, b"number-rows-repeated");
+                        }
+                    }
+                    b"table-cell" => {
+                        if let Some(table) = stack.last_mut() {
+                            table.cell = Some(Self::grid_cell(&e));
+                            table.cell_repeat = repeat(&e, b"number-columns-repeated");
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Empty(e) => match e.local_name().as_ref() {
+                    // 空单元格
+                    b"table-cell" => {
+                        if let Some(table) = stack.last_mut() {
+                            let cell = Self::grid_cell(&e);
+                            let count = repeat(&e, b"number-columns-repeated");
+                            if let Some(row) = table.rows.last_mut() {
+                                row.extend(std::iter::repeat_n(cell, count));
+                            }
+                        }
+                    }
+                    b"s" => {
+                        let count = repeat(&e, b"c");
+                        push_text(&mut stack, &mut text, &" ".repeat(count));
+                    }
+                    b"tab" => push_text(&mut stack, &mut text, " "),
+                    b"line-break" => push_text(&mut stack, &mut text, "\n"),
+                    _ => {}
+                },
+                Event::End(e) => match e.local_name().as_ref() {
+                    b"p" | b"h" => push_text(&mut stack, &mut text, "\n"),
+                    b"table-cell" => {
+                        if let Some(table) = stack.last_mut() {
+                            if let Some(mut cell) = table.cell.take() {
+                                cell.text = normalize_text(&cell.text);
+                                let count = table.cell_repeat;
+                                if let Some(row) = table.rows.last_mut() {
+                                    row.extend(std::iter::repeat_n(cell, count));
+                                }
+                            }
+                        }
+                    }
+                    b"table-row" => {
+                        if let Some(table) = stack.last_mut() {
+                            // 重复行只展开有内容的行，尾部大量空白行按一行处理
+                            let count = table.row_repeat;
+                            if let Some(row) = table.rows.last().cloned() {
+                                if row.iter().any(|cell| !cell.text.is_empty()) {
+                                    for _ in 1..count {
+                                        table.rows.push(row.clone());
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    b"table" => {
+                        if let Some(table) = stack.pop() {
+                            let (mut rows, mut merges) = grid::layout(table.rows);
+                            trim_trailing_empty(&mut rows, &mut merges);
+                            if !rows.is_empty() {
+                                done.push((table.order, ExtractedTable { rows, merges, ..Default::default() }));
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Text(t) => {
+                    let value = t.unescape().map_err(|e| e.to_string())?;
+                    // XML 中的换行和缩进不是正文内容
+                    let value: String = value.chars().map(|c| if c.is_whitespace() { ' ' } else { c }).collect();
+                    push_text(&mut stack, &mut text, &value);
+                }
+                Event::CData(t) => {
+                    push_text(&mut stack, &mut text, &String::from_utf8_lossy(&t));
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        done.sort_by_key(|(order, _)| *order);
+
+        Ok(DocumentContent {
+            tables: done.into_iter().map(|(_, table)| table).collect(),
+            text: normalize_text(&text),
+        })
+    }
+
+    /// 根据 table:table-cell 的跨度属性创建单元格
+    fn grid_cell(e: &BytesStart) -> GridCell {
+        GridCell {
+            text: String::new(),
+            row_span: repeat(e, b"number-rows-spanned"),
+            col_span: repeat(e, b"number-columns-spanned").min(MAX_REPEAT),
+        }
+    }
+}
+
+/// 文本追加到当前单元格；不在表格中时追加到正文
+/// 被合并覆盖的单元格（covered-table-cell）中的文本会被丢弃
+fn push_text(stack: &mut [TableBuilder], text: &mut String, value: &str) {
+    match stack.last_mut() {
+        Some(table) => {
+            if let Some(cell) = &mut table.cell {
+                cell.text.push_str(value);
+            }
+        }
+        None => text.push_str(value),
+    }
+}
+
+/// 读取重复次数/跨度属性，缺省为 1
+fn repeat(e: &BytesStart, name: &[u8]) -> usize {
+    attr(e, name)
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, MAX_REPEAT)
+}
+
+/// 按本地名读取属性值（忽略命名空间前缀）
+fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name)
+        .map(|a| String::from_utf8_lossy(&a.value).into_owned())
+}
+
+/// 去掉表格末尾的空白行和空白列（重复属性展开后常见）
+fn trim_trailing_empty(rows: &mut Vec<Vec<String>>, merges: &mut Vec<MergedRange>) {
+    while rows.last().is_some_and(|row| row.iter().all(|cell| cell.is_empty())) {
+        rows.pop();
+    }
+
+    let width = rows
+        .iter()
+        .map(|row| row.iter().rposition(|cell| !cell.is_empty()).map_or(0, |i| i + 1))
+        .max()
+        .unwrap_or(0);
+
+    for row in rows.iter_mut() {
+        row.truncate(width);
+    }
+
+    let height = rows.len();
+    merges.retain_mut(|m| {
+        if m.row >= height || m.col >= width {
+            return false;
+        }
+        m.row_span = m.row_span.min(height - m.row);
+        m.col_span = m.col_span.min(width - m.col);
+        m.row_span > 1 || m.col_span > 1
+    });
+}
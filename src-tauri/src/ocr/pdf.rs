@@ -2,7 +2,7 @@
 // 支持按页提取文本和简单表格检测
 
 use lopdf::{Document, Object, ObjectId};
-use crate::ocr::{PageText, DetectedTable, ExtractedTable};
+use crate::ocr::{PageText, DetectedTable, ExtractedTable, TableSource};
 
 /// PDF 处理器
 pub struct PdfProcessor;
@@ -16,17 +16,32 @@ impl PdfProcessor {
     /// # 返回
     /// 每页的文本内容列表
     pub fn extract_text(file_path: &str) -> Result<Vec<PageText>, String> {
+        Self::extract_text_with_progress(file_path, |_, _| Ok(()))
+    }
+
+    /// 从 PDF 提取文本（按页），每处理完一页回调一次
+    ///
+    /// # 参数
+    /// * `file_path` - PDF 文件路径
+    /// * `on_page` - 进度回调，参数为 (已处理页数, 总页数)；返回错误时停止提取（用于取消）
+    pub fn extract_text_with_progress<F>(file_path: &str, mut on_page: F) -> Result<Vec<PageText>, String>
+    where
+        F: FnMut(usize, usize) -> Result<(), String>,
+    {
         let doc = Document::load(file_path).map_err(|e| e.to_string())?;
 
+        let page_map = doc.get_pages();
+        let total = page_map.len();
         let mut pages = Vec::new();
 
         // 按页码顺序遍历所有页面
-        for (page_num, page_id) in doc.get_pages().keys().enumerate() {
+        for (page_num, page_id) in page_map.values().enumerate() {
             let text = Self::extract_page_text(&doc, page_id)?;
             pages.push(PageText {
                 page: page_num as u32 + 1,
                 text,
             });
+            on_page(page_num + 1, total)?;
         }
 
         Ok(pages)
@@ -107,6 +122,7 @@ impl PdfProcessor {
     /// 按行分割，每行作为一行数据
     pub fn text_to_table(pages: Vec<PageText>) -> ExtractedTable {
         let mut all_rows: Vec<Vec<String>> = Vec::new();
+        let mut source = TableSource::default();
 
         for page in pages {
             let lines: Vec<Vec<String>> = page
@@ -115,9 +131,18 @@ impl PdfProcessor {
                 .filter(|line| !line.trim().is_empty())
                 .map(|line| vec![line.trim().to_string()])
                 .collect();
+
+            // 记录有内容的起止页
+            if !lines.is_empty() {
+                match source.page {
+                    None => source.page = Some(page.page),
+                    Some(first) if first != page.page => source.end_page = Some(page.page),
+                    Some(_) => {}
+                }
+            }
             all_rows.extend(lines);
         }
 
-        ExtractedTable { rows: all_rows }
+        ExtractedTable { rows: all_rows, source, ..Default::default() }
     }
 }
@@ -0,0 +1,272 @@
+// PowerPoint 解析模块 - 提取 .pptx 幻灯片中的表格
+// 按演示文稿中的幻灯片顺序遍历图形框（graphicFrame）中的 a:tbl 表格，记录所在幻灯片编号和标题
+
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use quick_xml::events::{BytesStart, Event};
+use crate::ocr::grid::{self, normalize_text, GridCell};
+use crate::ocr::{ExtractedTable, TableSource};
+
+/// PowerPoint 处理器
+pub struct PptxProcessor;
+
+/// 幻灯片中正在构建的表格
+#[derive(Default)]
+struct TableBuilder {
+    rows: Vec<Vec<GridCell>>,
+    cell: Option<GridCell>,
+    covered: bool,   // 当前单元格是否被合并覆盖（hMerge/vMerge）
+}
+
+impl PptxProcessor {
+    /// 从 PPTX 文件提取所有表格
+    ///
+    /// # 参数
+    /// * `file_path` - PPTX 文件路径
+    ///
+    /// # 返回
+    /// 按幻灯片顺序排列的表格列表，`source.slide` 为幻灯片编号（从 1 开始），
+    /// `source.slide_title` 为幻灯片标题
+    pub fn extract_tables(file_path: &str) -> Result<Vec<ExtractedTable>, String> {
+        let file = std::fs::File::open(file_path).map_err(|e| e.to_string())?;
+        let mut archive = zip::ZipArchive::new(std::io::BufReader::new(file)).map_err(|e| e.to_string())?;
+
+        let slides = Self::slide_paths(&mut archive)?;
+        let mut tables = Vec::new();
+
+        for (index, path) in slides.iter().enumerate() {
+            let Some(xml) = read_part(&mut archive, path)? else { continue };
+            let (title, slide_tables) = Self::parse_slide(&xml)?;
+
+            for mut table in slide_tables {
+                table.source = TableSource {
+                    slide: Some(index as u32 + 1),
+                    slide_title: title.clone(),
+                    ..Default::default()
+                };
+                tables.push(table);
+            }
+        }
+
+        Ok(tables)
+    }
+
+    /// 按演示文稿顺序列出幻灯片部件路径
+    fn slide_paths<R: Read + Seek>(archive: &mut zip::ZipArchive<R>) -> Result<Vec<String>, String> {
+        let mut targets: HashMap<String, String> = HashMap::new();
+
+        if let Some(xml) = read_part(archive, "ppt/_rels/presentation.xml.rels")? {
+            let mut reader = quick_xml::Reader::from_reader(xml.as_slice());
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+                    Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                        if let (Some(id), Some(target)) = (attr(&e, b"Id"), attr(&e, b"Target")) {
+                            let path = match target.strip_prefix('/') {
+                                Some(absolute) => absolute.to_string(),
+                                None => format!("ppt/{}", target),
+                            };
+                            targets.insert(id, path);
+                        }
+                    }
+                    Event::Eof => break,
+                    _ => {}
+                }
+            }
+        }
+
+        let Some(xml) = read_part(archive, "ppt/presentation.xml")? else {
+            return Err("PPTX 文件中未找到 ppt/presentation.xml".to_string());
+        };
+
+        let mut reader = quick_xml::Reader::from_reader(xml.as_slice());
+        let mut slides = Vec::new();
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            match reader.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+                Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sldId" => {
+                    // 关系 ID 为带命名空间前缀的 r:id，无前缀的 id 是幻灯片编号
+                    let rel_id = e
+                        .attributes()
+                        .flatten()
+                        .find(|a| a.key.prefix().is_some() && a.key.local_name().as_ref() == b"id")
+                        .map(|a| String::from_utf8_lossy(&a.value).into_owned());
+                    if let Some(path) = rel_id.and_then(|id| targets.get(&id)) {
+                        slides.push(path.clone());
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(slides)
+    }
+
+    /// 解析单张幻灯片
+    ///
+    /// # 返回
+    /// (幻灯片标题, 表格列表)
+    fn parse_slide(xml: &[u8]) -> Result<(Option<String>, Vec<ExtractedTable>), String> {
+        let mut reader = quick_xml::Reader::from_reader(xml);
+        reader.trim_text(false);
+
+        let mut title: Option<String> = None;
+        let mut tables = Vec::new();
+
+        // 当前形状（p:sp）的文本和是否为标题占位符
+        let mut shape_text: Option<String> = None;
+        let mut shape_is_title = false;
+        let mut table: Option<TableBuilder> = None;
+        let mut in_text = false;
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            match reader.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+                Event::Start(e) => match e.local_name().as_ref() {
+                    b"sp" => {
+                        shape_text = Some(String::new());
+                        shape_is_title = false;
+                    }
+                    b"ph" => shape_is_title |= is_title_placeholder(&e),
+                    b"tbl" => table = Some(TableBuilder::default()),
+                    b"tr" => {
+                        if let Some(table) = &mut table {
+                            table.rows.push(Vec::new());
+                        }
+                    }
+                    b"tc" => {
+                        if let Some(table) = &mut table {
+                            table.covered = is_covered(&e);
+                            table.cell = Some(grid_cell(&e));
+                        }
+                    }
+                    b"t" => in_text = true,
+                    _ => {}
+                },
+                Event::Empty(e) => match e.local_name().as_ref() {
+                    b"ph" => shape_is_title |= is_title_placeholder(&e),
+                    b"tc" => {
+                        if let Some(table) = &mut table {
+                            if !is_covered(&e) {
+                                if let Some(row) = table.rows.last_mut() {
+                                    row.push(grid_cell(&e));
+                                }
+                            }
+                        }
+                    }
+                    b"br" => push_text(&mut table, &mut shape_text, "\n"),
+                    _ => {}
+                },
+                Event::End(e) => match e.local_name().as_ref() {
+                    b"t" => in_text = false,
+                    b"p" => push_text(&mut table, &mut shape_text, "\n"),
+                    b"tc" => {
+                        if let Some(table) = &mut table {
+                            if let Some(mut cell) = table.cell.take() {
+                                if !table.covered {
+                                    cell.text = normalize_text(&cell.text);
+                                    if let Some(row) = table.rows.last_mut() {
+                                        row.push(cell);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    b"tbl" => {
+                        if let Some(builder) = table.take() {
+                            let (rows, merges) = grid::layout(builder.rows);
+                            if !rows.is_empty() {
+                                tables.push(ExtractedTable { rows, merges, ..Default::default() });
+                            }
+                        }
+                    }
+                    b"sp" => {
+                        if let Some(text) = shape_text.take() {
+                            let text = normalize_text(&text).replace('\n', " ");
+                            if shape_is_title && title.is_none() && !text.is_empty() {
+                                title = Some(text);
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Text(t) if in_text => {
+                    let value = t.unescape().map_err(|e| e.to_string())?;
+                    push_text(&mut table, &mut shape_text, &value);
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok((title, tables))
+    }
+}
+
+/// 文本追加到当前表格单元格，不在表格中时追加到当前形状
+fn push_text(table: &mut Option<TableBuilder>, shape_text: &mut Option<String>, value: &str) {
+    if let Some(table) = table {
+        if let Some(cell) = &mut table.cell {
+            cell.text.push_str(value);
+        }
+    } else if let Some(text) = shape_text {
+        text.push_str(value);
+    }
+}
+
+/// 根据 a:tc 的 gridSpan/rowSpan 属性创建单元格
+fn grid_cell(e: &BytesStart) -> GridCell {
+    let span = |name: &[u8]| attr(e, name).and_then(|v| v.parse::<usize>().ok()).unwrap_or(1).max(1);
+    GridCell {
+        text: String::new(),
+        row_span: span(b"rowSpan"),
+        col_span: span(b"gridSpan"),
+    }
+}
+
+/// 单元格是否被左侧或上方的合并单元格覆盖
+fn is_covered(e: &BytesStart) -> bool {
+    let flag = |name: &[u8]| matches!(attr(e, name).as_deref(), Some("1") | Some("true"));
+    flag(b"hMerge") || flag(b"vMerge")
+}
+
+/// 是否为标题占位符
+fn is_title_placeholder(e: &BytesStart) -> bool {
+    matches!(attr(e, b"type").as_deref(), Some("title") | Some("ctrTitle"))
+}
+
+/// 读取压缩包中的部件（部件名不区分大小写），不存在时返回 None
+fn read_part<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, path: &str) -> Result<Option<Vec<u8>>, String> {
+    let Some(name) = archive
+        .file_names()
+        .find(|n| n.eq_ignore_ascii_case(path))
+        .map(|n| n.to_string())
+    else {
+        return Ok(None);
+    };
+
+    let mut bytes = Vec::new();
+    archive
+        .by_name(&name)
+        .map_err(|e| e.to_string())?
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+
+    Ok(Some(bytes))
+}
+
+/// 按本地名读取属性值（忽略命名空间前缀）
+fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name)
+        .and_then(|a| {
+            let value = String::from_utf8_lossy(&a.value).into_owned();
+            quick_xml::escape::unescape(&value).ok().map(|v| v.into_owned())
+        })
+}
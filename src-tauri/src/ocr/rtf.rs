@@ -0,0 +1,496 @@
+// RTF 解析模块 - 读取富文本格式文档中的表格和正文
+// 按单元格右边界（\cellx）还原列结构，支持横向（\clmgf/\clmrg）和纵向（\clvmgf/\clvmrg）合并
+
+use std::collections::HashMap;
+use encoding_rs::Encoding;
+use crate::ocr::grid::normalize_text;
+use crate::ocr::{DocumentContent, ExtractedTable, MergedRange};
+
+/// RTF 文档处理器
+pub struct RtfProcessor;
+
+/// 需要整体跳过的目标组（非正文内容）
+const SKIP_DESTINATIONS: &[&str] = &[
+    "colortbl", "stylesheet", "info", "pict", "object", "header", "headerl", "headerr",
+    "headerf", "footer", "footerl", "footerr", "footerf", "footnote", "fldinst", "listtable",
+    "listoverridetable", "rsidtbl", "generator", "xmlnstbl", "themedata", "colorschememapping",
+    "datastore", "latentstyles", "pgdsctbl", "revtbl", "filetbl", "annotation", "atnid", "atnauthor",
+];
+
+/// 合并标记
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Merge {
+    #[default]
+    None,
+    First,      // 合并区域的起始单元格
+    Continue,   // 被合并的后续单元格
+}
+
+/// 单元格定义（\trowd 到 \cellx 之间的属性）
+#[derive(Debug, Clone, Copy, Default)]
+struct CellDef {
+    right: i32,        // 右边界（twip）
+    hmerge: Merge,
+    vmerge: Merge,
+}
+
+/// 表格行：单元格文本与定义
+struct RowData {
+    left: i32,
+    cells: Vec<(String, CellDef)>,
+}
+
+/// 组状态（随 `{` `}` 压栈/出栈）
+#[derive(Debug, Clone, Default)]
+struct Group {
+    skip: bool,        // 是否处于被跳过的目标组
+    in_fonttbl: bool,  // 是否处于字体表
+    uc: usize,         // \uN 之后需跳过的替代字符数
+    font: Option<i32>,
+}
+
+/// 解析状态
+#[derive(Default)]
+struct Parser {
+    groups: Vec<Group>,
+    group: Group,
+    default_codepage: u32,
+    default_font: Option<i32>,
+    font_charsets: HashMap<i32, u32>,
+    defining_font: Option<i32>,
+    pending_bytes: Vec<u8>,   // \'hh 字节，按当前字体代码页统一解码
+    skip_chars: usize,
+    paragraph: String,
+    in_table: bool,
+    row_def: Vec<CellDef>,
+    cell_def: CellDef,
+    row_left: i32,
+    row_cells: Vec<String>,
+    table: Vec<RowData>,
+    content: DocumentContent,
+}
+
+impl RtfProcessor {
+    /// 从 RTF 文件提取表格
+    ///
+    /// # 参数
+    /// * `file_path` - RTF 文件路径
+    ///
+    /// # 返回
+    /// 表格列表，正文段落不为空时额外追加一个单列表格（每段一行）
+    pub fn extract_tables(file_path: &str) -> Result<Vec<ExtractedTable>, String> {
+        Ok(Self::parse_file(file_path)?.into_tables())
+    }
+
+    /// 解析 RTF 文件
+    pub fn parse_file(file_path: &str) -> Result<DocumentContent, String> {
+        let bytes = std::fs::read(file_path).map_err(|e| e.to_string())?;
+
+        if !bytes.starts_with(b"{\\rtf") {
+            return Err("不是有效的 RTF 文件".to_string());
+        }
+
+        Ok(Self::parse(&bytes))
+    }
+
+    /// 解析 RTF 内容
+    pub fn parse(data: &[u8]) -> DocumentContent {
+        let mut parser = Parser {
+            default_codepage: 1252,
+            group: Group { uc: 1, ..Default::default() },
+            ..Default::default()
+        };
+
+        let mut i = 0;
+
+        while i < data.len() {
+            match data[i] {
+                b'{' => {
+                    parser.flush_bytes();
+                    parser.groups.push(parser.group.clone());
+                    i += 1;
+                }
+                b'}' => {
+                    parser.flush_bytes();
+                    if let Some(group) = parser.groups.pop() {
+                        parser.group = group;
+                    }
+                    i += 1;
+                }
+                b'\\' => {
+                    i = parser.control(data, i + 1);
+                }
+                // 源码中的换行不是正文内容
+                b'\r' | b'\n' => i += 1,
+                c => {
+                    parser.flush_bytes();
+                    parser.push_char(c as char);
+                    i += 1;
+                }
+            }
+        }
+
+        parser.flush_bytes();
+        parser.end_paragraph();
+        parser.finish_table();
+
+        let mut content = parser.content;
+        content.text = normalize_text(&content.text);
+        content
+    }
+}
+
+impl Parser {
+    /// 解析控制字/控制符，返回下一个待处理位置
+    fn control(&mut self, data: &[u8], start: usize) -> usize {
+        let Some(&first) = data.get(start) else { return start };
+
+        // 控制符
+        if !first.is_ascii_alphabetic() {
+            return match first {
+                b'\'' => {
+                    let hex = data.get(start + 1..start + 3).and_then(|h| std::str::from_utf8(h).ok());
+                    if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                        self.push_byte(byte);
+                    }
+                    start + 3
+                }
+                b'*' => {
+                    self.group.skip = true;
+                    start + 1
+                }
+                b'~' => {
+                    self.flush_bytes();
+                    self.push_char(' ');
+                    start + 1
+                }
+                b'_' => {
+                    self.flush_bytes();
+                    self.push_char('-');
+                    start + 1
+                }
+                b'\\' | b'{' | b'}' => {
+                    self.flush_bytes();
+                    self.push_char(first as char);
+                    start + 1
+                }
+                // \<换行> 等同于 \par
+                b'\r' | b'\n' => {
+                    self.flush_bytes();
+                    self.paragraph_break();
+                    start + 1
+                }
+                _ => start + 1,
+            };
+        }
+
+        let mut end = start;
+        while end < data.len() && data[end].is_ascii_alphabetic() {
+            end += 1;
+        }
+        let word = String::from_utf8_lossy(&data[start..end]).into_owned();
+
+        let num_start = end;
+        if end < data.len() && data[end] == b'-' {
+            end += 1;
+        }
+        while end < data.len() && data[end].is_ascii_digit() {
+            end += 1;
+        }
+        let param: Option<i32> = std::str::from_utf8(&data[num_start..end]).ok().and_then(|n| n.parse().ok());
+
+        // 控制字后的一个空格是分隔符
+        if end < data.len() && data[end] == b' ' {
+            end += 1;
+        }
+
+        if word == "bin" {
+            return end + param.unwrap_or(0).max(0) as usize;
+        }
+
+        self.flush_bytes();
+        self.control_word(&word, param);
+        end
+    }
+
+    /// 处理控制字
+    fn control_word(&mut self, word: &str, param: Option<i32>) {
+        if SKIP_DESTINATIONS.contains(&word) {
+            self.group.skip = true;
+            return;
+        }
+
+        match word {
+            "fonttbl" => self.group.in_fonttbl = true,
+            "f" if self.group.in_fonttbl => self.defining_font = param,
+            "f" => self.group.font = param,
+            "fcharset" if self.group.in_fonttbl => {
+                if let (Some(font), Some(charset)) = (self.defining_font, param) {
+                    self.font_charsets.insert(font, charset as u32);
+                }
+            }
+            "ansicpg" => self.default_codepage = param.unwrap_or(1252) as u32,
+            "deff" => self.default_font = param,
+            "uc" => self.group.uc = param.unwrap_or(1).max(0) as usize,
+            "u" => {
+                if let Some(code) = param {
+                    let code = if code < 0 { code + 65536 } else { code } as u32;
+                    if let Some(c) = char::from_u32(code) {
+                        self.push_char(c);
+                    }
+                    self.skip_chars = self.group.uc;
+                }
+            }
+            "par" => self.paragraph_break(),
+            "line" => self.push_char('\n'),
+            "tab" => self.push_char(' '),
+            "emdash" => self.push_char('—'),
+            "endash" => self.push_char('–'),
+            "bullet" => self.push_char('•'),
+            "lquote" => self.push_char('‘'),
+            "rquote" => self.push_char('’'),
+            "ldblquote" => self.push_char('“'),
+            "rdblquote" => self.push_char('”'),
+            // 嵌套表格不单独还原，内容并入外层单元格
+            "nestcell" => self.push_char(' '),
+            "nestrow" => self.push_char('\n'),
+            "pard" => self.in_table = false,
+            "intbl" => self.in_table = true,
+            "trowd" => {
+                self.row_def.clear();
+                self.cell_def = CellDef::default();
+                self.row_left = 0;
+            }
+            "trleft" => self.row_left = param.unwrap_or(0),
+            "clmgf" => self.cell_def.hmerge = Merge::First,
+            "clmrg" => self.cell_def.hmerge = Merge::Continue,
+            "clvmgf" => self.cell_def.vmerge = Merge::First,
+            "clvmrg" => self.cell_def.vmerge = Merge::Continue,
+            "cellx" => {
+                self.cell_def.right = param.unwrap_or(0);
+                self.row_def.push(self.cell_def);
+                self.cell_def = CellDef::default();
+            }
+            "cell" => {
+                let text = normalize_text(&std::mem::take(&mut self.paragraph));
+                self.row_cells.push(text);
+            }
+            "row" => self.finish_row(),
+            _ => {}
+        }
+    }
+
+    /// 追加 \'hh 字节（可能是多字节字符的一部分）
+    fn push_byte(&mut self, byte: u8) {
+        if self.skip_chars > 0 {
+            self.skip_chars -= 1;
+            return;
+        }
+        self.pending_bytes.push(byte);
+    }
+
+    /// 按当前字体的代码页解码待处理字节
+    fn flush_bytes(&mut self) {
+        if self.pending_bytes.is_empty() {
+            return;
+        }
+
+        let bytes = std::mem::take(&mut self.pending_bytes);
+        let codepage = self
+            .group
+            .font
+            .or(self.default_font)
+            .and_then(|font| self.font_charsets.get(&font))
+            .and_then(|charset| charset_codepage(*charset))
+            .unwrap_or(self.default_codepage);
+
+        let encoding = codepage_encoding(codepage);
+        let (text, _) = encoding.decode_without_bom_handling(&bytes);
+        for c in text.chars() {
+            self.push_text_char(c);
+        }
+    }
+
+    /// 追加普通字符（\uN 的替代字符会被跳过）
+    fn push_char(&mut self, c: char) {
+        if self.skip_chars > 0 {
+            self.skip_chars -= 1;
+            return;
+        }
+        self.push_text_char(c);
+    }
+
+    fn push_text_char(&mut self, c: char) {
+        if self.group.skip || self.group.in_fonttbl {
+            return;
+        }
+        self.paragraph.push(c);
+    }
+
+    /// 段落结束：表格内为单元格内换行，表格外结束当前表格
+    fn paragraph_break(&mut self) {
+        if self.group.skip || self.group.in_fonttbl {
+            return;
+        }
+
+        if self.in_table {
+            self.paragraph.push('\n');
+        } else {
+            self.end_paragraph();
+        }
+    }
+
+    /// 结束表格外的段落
+    fn end_paragraph(&mut self) {
+        self.finish_table();
+        let paragraph = std::mem::take(&mut self.paragraph);
+        self.content.text.push_str(&paragraph);
+        self.content.text.push('\n');
+    }
+
+    /// 行结束：单元格文本与行定义按顺序配对
+    /// 行定义可能出现在单元格内容之前或 \row 之前，使用最近一次的定义
+    fn finish_row(&mut self) {
+        let texts = std::mem::take(&mut self.row_cells);
+        if texts.is_empty() {
+            return;
+        }
+
+        let mut last_right = self.row_def.last().map(|d| d.right).unwrap_or(self.row_left);
+        let cells = texts
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let def = self.row_def.get(i).copied().unwrap_or_else(|| {
+                    // 缺少定义的单元格按 1 英寸宽度补齐
+                    last_right += 1440;
+                    CellDef { right: last_right, ..Default::default() }
+                });
+                (text, def)
+            })
+            .collect();
+
+        self.table.push(RowData { left: self.row_left, cells });
+    }
+
+    /// 将累积的表格行转换为表格
+    fn finish_table(&mut self) {
+        let rows = std::mem::take(&mut self.table);
+        if rows.is_empty() {
+            return;
+        }
+
+        // 所有行的单元格边界合并后作为列分隔线
+        let mut bounds: Vec<i32> = Vec::new();
+
+        for row in &rows {
+            let mut left = row.left;
+            for (_, def) in &row.cells {
+                let right = def.right.max(left + 1);
+                bounds.push(left);
+                bounds.push(right);
+                left = right;
+            }
+        }
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let column = |x: i32| bounds.binary_search(&x).unwrap_or_else(|i| i);
+        let width = bounds.len().saturating_sub(1);
+
+        // (行, 列, 跨行, 跨列, 文本)
+        let mut placed: Vec<(usize, usize, usize, usize, String)> = Vec::new();
+        let mut vertical_origins: HashMap<usize, usize> = HashMap::new();
+
+        for (r, row) in rows.into_iter().enumerate() {
+            let mut left = row.left;
+            let mut previous: Option<usize> = None;
+
+            for (text, def) in row.cells {
+                let right = def.right.max(left + 1);
+                let col = column(left);
+                let col_span = column(right).saturating_sub(col).max(1);
+                left = right;
+
+                if def.hmerge == Merge::Continue {
+                    if let Some(p) = previous {
+                        placed[p].3 = col + col_span - placed[p].1;
+                        continue;
+                    }
+                }
+
+                if def.vmerge == Merge::Continue {
+                    if let Some(&origin) = vertical_origins.get(&col) {
+                        placed[origin].2 = r - placed[origin].0 + 1;
+                        previous = None;
+                        continue;
+                    }
+                }
+
+                placed.push((r, col, 1, col_span, text));
+                let index = placed.len() - 1;
+                if def.vmerge == Merge::First {
+                    vertical_origins.insert(col, index);
+                } else {
+                    vertical_origins.remove(&col);
+                }
+                previous = Some(index);
+            }
+        }
+
+        let height = placed.iter().map(|p| p.0 + p.2).max().unwrap_or(0);
+        let mut grid = vec![vec![String::new(); width]; height];
+        let mut merges = Vec::new();
+
+        for (row, col, row_span, col_span, text) in placed {
+            for line in grid.iter_mut().skip(row).take(row_span) {
+                for cell in line.iter_mut().skip(col).take(col_span) {
+                    *cell = text.clone();
+                }
+            }
+            if row_span > 1 || col_span > 1 {
+                merges.push(MergedRange { row, col, row_span, col_span });
+            }
+        }
+
+        self.content.tables.push(ExtractedTable { rows: grid, merges, ..Default::default() });
+    }
+}
+
+/// 字体字符集（\fcharset）对应的代码页
+fn charset_codepage(charset: u32) -> Option<u32> {
+    let codepage = match charset {
+        0 => 1252,
+        128 => 932,
+        129 => 949,
+        134 => 936,
+        136 => 950,
+        161 => 1253,
+        162 => 1254,
+        177 => 1255,
+        178 => 1256,
+        186 => 1257,
+        204 => 1251,
+        222 => 874,
+        238 => 1250,
+        // 1 表示使用默认代码页，2 为符号字体
+        _ => return None,
+    };
+    Some(codepage)
+}
+
+/// 代码页对应的编码，未知代码页按 Windows-1252 处理
+fn codepage_encoding(codepage: u32) -> &'static Encoding {
+    let label = match codepage {
+        874 => "windows-874".to_string(),
+        932 => "shift_jis".to_string(),
+        // GBK 文档常含 GB18030 扩展字符，统一按超集解码
+        936 => "gb18030".to_string(),
+        949 => "euc-kr".to_string(),
+        950 => "big5".to_string(),
+        65001 => "utf-8".to_string(),
+        10000 => "macintosh".to_string(),
+        cp => format!("windows-{}", cp),
+    };
+    Encoding::for_label(label.as_bytes()).unwrap_or(encoding_rs::WINDOWS_1252)
+}
@@ -1,7 +1,13 @@
 // Word 解析模块 - 使用 docx-rs 提取 Word 文档中的表格
 // 解析 .docx 格式文档，提取所有表格数据
+// 支持识别复选框字形（☐/☑）、旧式 FORMCHECKBOX 域和内容控件（下拉列表、日期选择器、复选框）
 
-use docx_rs::{Docx, Table, TableRow, TableCell};
+use std::io::Read;
+use docx_rs::{
+    DocumentChild, InstrText, Paragraph, ParagraphChild, Run, RunChild,
+    StructuredDataTag, StructuredDataTagChild, Table, TableCell, TableCellContent, TableChild,
+    TableRow, TableRowChild,
+};
 use crate::ocr::ExtractedTable;
 
 /// Word 文档处理器
@@ -15,6 +21,36 @@ struct CellData {
     col_span: u32,
 }
 
+/// 未选中复选框字形（含 Wingdings 私有区字符）
+const UNCHECKED_GLYPHS: &[char] = &['☐', '□', '\u{F06F}', '\u{F0A8}'];
+
+/// 已选中复选框字形（含 Wingdings 私有区字符）
+const CHECKED_GLYPHS: &[char] = &['☑', '☒', '\u{F0FE}', '\u{F078}'];
+
+/// 内容控件未填写时显示的占位文本
+const SDT_PLACEHOLDERS: &[&str] = &[
+    "单击或点击此处输入文字。",
+    "单击或点击以输入日期。",
+    "选择一项。",
+    "Click or tap here to enter text.",
+    "Click or tap to enter a date.",
+    "Choose an item.",
+];
+
+/// 表单域解析状态
+/// docx-rs 不解析 w:ffData，旧式复选框的勾选状态需预先从 document.xml 中读取，
+/// 遍历文档时按出现顺序依次消费
+struct FormState {
+    legacy_checkboxes: std::vec::IntoIter<bool>,
+}
+
+impl FormState {
+    /// 取出下一个旧式复选框的勾选状态
+    fn next_legacy_checkbox(&mut self) -> bool {
+        self.legacy_checkboxes.next().unwrap_or(false)
+    }
+}
+
 impl WordProcessor {
     /// 从 Word 文档提取所有表格
     ///
@@ -22,40 +58,86 @@ impl WordProcessor {
     /// * `file_path` - Word 文件路径（.docx）
     ///
     /// # 返回
-    /// 提取的表格列表
+    /// 提取的表格列表，复选框等表单控件会被归一化为选中项文本或 true/false
     pub fn extract_tables(file_path: &str) -> Result<Vec<ExtractedTable>, String> {
-        let file = std::fs::File::open(file_path).map_err(|e| e.to_string())?;
+        let bytes = std::fs::read(file_path).map_err(|e| e.to_string())?;
+
+        let doc = docx_rs::read_docx(&bytes).map_err(|e| e.to_string())?;
 
-        let doc = Docx::read(file).map_err(|e| e.to_string())?;
+        let mut state = FormState {
+            legacy_checkboxes: Self::read_legacy_checkbox_states(&bytes)?.into_iter(),
+        };
 
         let mut tables = Vec::new();
+        let mut text = String::new();
+        Self::walk_body(&doc.document.children, &mut state, &mut tables, &mut text)?;
+
+        Ok(tables)
+    }
 
-        for table in doc.tables {
-            let extracted = Self::parse_table(&table)?;
-            tables.push(extracted);
+    /// 按文档顺序遍历正文，表格加入 `tables`，段落文本加入 `text`（每段一行）
+    /// 正文和内容控件中的旧式复选框都需消费，保证状态顺序对齐
+    fn walk_body(
+        children: &[DocumentChild],
+        state: &mut FormState,
+        tables: &mut Vec<ExtractedTable>,
+        text: &mut String,
+    ) -> Result<(), String> {
+        for child in children {
+            match child {
+                DocumentChild::Table(table) => tables.push(Self::parse_table(table, state)?),
+                DocumentChild::Paragraph(paragraph) => {
+                    text.push_str(&Self::paragraph_text(paragraph, state));
+                    text.push('\n');
+                }
+                DocumentChild::StructuredDataTag(sdt) => Self::walk_body_sdt(sdt, state, tables, text)?,
+                _ => {}
+            }
         }
 
-        Ok(tables)
+        Ok(())
+    }
+
+    /// 遍历正文级内容控件（可包含段落和整张表格）
+    fn walk_body_sdt(
+        sdt: &StructuredDataTag,
+        state: &mut FormState,
+        tables: &mut Vec<ExtractedTable>,
+        text: &mut String,
+    ) -> Result<(), String> {
+        for child in &sdt.children {
+            match child {
+                StructuredDataTagChild::Table(table) => tables.push(Self::parse_table(table, state)?),
+                StructuredDataTagChild::Paragraph(paragraph) => {
+                    text.push_str(&Self::paragraph_text(paragraph, state));
+                    text.push('\n');
+                }
+                StructuredDataTagChild::Run(run) => text.push_str(&Self::run_text(run, state)),
+                _ => {}
+            }
+        }
+
+        Ok(())
     }
 
     /// 解析单个表格
-    fn parse_table(table: &Table) -> Result<ExtractedTable, String> {
+    fn parse_table(table: &Table, state: &mut FormState) -> Result<ExtractedTable, String> {
         let mut rows = Vec::new();
 
-        for row in &table.rows {
-            let row_data = Self::parse_row(row)?;
+        for TableChild::TableRow(row) in &table.rows {
+            let row_data = Self::parse_row(row, state)?;
             rows.push(row_data);
         }
 
-        Ok(ExtractedTable { rows })
+        Ok(ExtractedTable { rows, ..Default::default() })
     }
 
     /// 解析表格行
-    fn parse_row(row: &TableRow) -> Result<Vec<String>, String> {
+    fn parse_row(row: &TableRow, state: &mut FormState) -> Result<Vec<String>, String> {
         let mut cells = Vec::new();
 
-        for cell in &row.cells {
-            let text = Self::parse_cell(cell)?;
+        for TableRowChild::TableCell(cell) in &row.cells {
+            let text = Self::parse_cell(cell, state)?;
             cells.push(text);
         }
 
@@ -63,16 +145,23 @@ impl WordProcessor {
     }
 
     /// 解析表格单元格
-    fn parse_cell(cell: &TableCell) -> Result<String, String> {
+    /// 先拼接单元格内文本（表单控件转为复选框字形），再归一化为表单值
+    fn parse_cell(cell: &TableCell, state: &mut FormState) -> Result<String, String> {
         let mut text = String::new();
 
         // 提取单元格内的所有段落文本
-        for paragraph in &cell.children {
-            for run in &paragraph.children {
-                // 提取文本内容
-                if let Some(text_run) = run.text.as_ref() {
-                    text.push_str(&text_run.text);
+        for content in &cell.children {
+            match content {
+                TableCellContent::Paragraph(paragraph) => {
+                    text.push_str(&Self::paragraph_text(paragraph, state));
                 }
+                TableCellContent::StructuredDataTag(sdt) => {
+                    text.push_str(&Self::sdt_text(sdt, state));
+                }
+                TableCellContent::Table(table) => {
+                    text.push_str(&Self::nested_table_text(table, state));
+                }
+                _ => {}
             }
             // 段落之间添加空格
             if !text.is_empty() {
@@ -80,27 +169,240 @@ impl WordProcessor {
             }
         }
 
-        Ok(text.trim().to_string())
+        Ok(Self::normalize_form_value(text.trim()))
     }
 
-    /// 提取文档中所有文本（不分表格）
-    pub fn extract_all_text(file_path: &str) -> Result<String, String> {
-        let file = std::fs::File::open(file_path).map_err(|e| e.to_string())?;
+    /// 将单元格内嵌套的表格展开为文本，单元格之间以空格分隔
+    fn nested_table_text(table: &Table, state: &mut FormState) -> String {
+        let Ok(nested) = Self::parse_table(table, state) else {
+            return String::new();
+        };
 
-        let doc = Docx::read(file).map_err(|e| e.to_string())?;
+        nested
+            .rows
+            .iter()
+            .flatten()
+            .map(|cell| cell.trim())
+            .filter(|cell| !cell.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
 
-        let mut all_text = String::new();
+    /// 提取段落文本
+    fn paragraph_text(paragraph: &Paragraph, state: &mut FormState) -> String {
+        let mut text = String::new();
+
+        for child in &paragraph.children {
+            match child {
+                ParagraphChild::Run(run) => text.push_str(&Self::run_text(run, state)),
+                ParagraphChild::StructuredDataTag(sdt) => text.push_str(&Self::sdt_text(sdt, state)),
+                _ => {}
+            }
+        }
+
+        text
+    }
+
+    /// 提取内容控件文本
+    /// 下拉列表和日期选择器的当前值即为其显示内容，复选框控件以字形显示
+    fn sdt_text(sdt: &StructuredDataTag, state: &mut FormState) -> String {
+        let mut text = String::new();
+
+        for child in &sdt.children {
+            match child {
+                StructuredDataTagChild::Run(run) => text.push_str(&Self::run_text(run, state)),
+                StructuredDataTagChild::Paragraph(paragraph) => {
+                    text.push_str(&Self::paragraph_text(paragraph, state));
+                }
+                StructuredDataTagChild::Table(table) => {
+                    text.push_str(&Self::nested_table_text(table, state));
+                }
+                _ => {}
+            }
+        }
+
+        // 未填写的控件只显示占位文本，视为空值
+        if SDT_PLACEHOLDERS.contains(&text.trim()) {
+            return String::new();
+        }
 
-        // 提取所有段落的文本
-        for paragraph in doc.paragraphs {
-            for run in &paragraph.children {
-                if let Some(text_run) = run.text.as_ref() {
-                    all_text.push_str(&text_run.text);
+        text
+    }
+
+    /// 提取文本块内容
+    fn run_text(run: &Run, state: &mut FormState) -> String {
+        let mut text = String::new();
+
+        for child in &run.children {
+            match child {
+                RunChild::Text(t) => text.push_str(&t.text),
+                RunChild::Tab(_) => text.push(' '),
+                RunChild::Sym(sym) => {
+                    if let Some(c) = Self::symbol_char(&sym.char) {
+                        text.push(c);
+                    }
+                }
+                RunChild::InstrText(instr) => {
+                    if let InstrText::Unsupported(code) = instr.as_ref() {
+                        Self::push_field_code(code, &mut text, state);
+                    }
+                }
+                RunChild::InstrTextString(code) => {
+                    Self::push_field_code(code, &mut text, state);
                 }
+                _ => {}
             }
-            all_text.push('\n');
         }
 
+        text
+    }
+
+    /// 处理域代码，旧式复选框域转为对应字形
+    fn push_field_code(code: &str, text: &mut String, state: &mut FormState) {
+        if code.trim().eq_ignore_ascii_case("FORMCHECKBOX") {
+            let glyph = if state.next_legacy_checkbox() { '☑' } else { '☐' };
+            text.push(glyph);
+        }
+    }
+
+    /// 将 w:sym 的十六进制字符码转为字符
+    /// Wingdings 等符号字体的字符位于私有区（F000-F0FF），只保留复选框相关字形
+    fn symbol_char(code: &str) -> Option<char> {
+        let value = u32::from_str_radix(code.trim(), 16).ok()?;
+        // 部分文档省略 F0 前缀
+        let value = if value < 0x100 { value + 0xF000 } else { value };
+        let c = char::from_u32(value)?;
+
+        if CHECKED_GLYPHS.contains(&c) || UNCHECKED_GLYPHS.contains(&c) {
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    /// 预读 document.xml 中旧式复选框（FORMCHECKBOX）的勾选状态
+    ///
+    /// # 返回
+    /// 按文档顺序排列的勾选状态
+    fn read_legacy_checkbox_states(bytes: &[u8]) -> Result<Vec<bool>, String> {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+            .map_err(|e| e.to_string())?;
+
+        let mut xml = String::new();
+        archive
+            .by_name("word/document.xml")
+            .map_err(|e| e.to_string())?
+            .read_to_string(&mut xml)
+            .map_err(|e| e.to_string())?;
+
+        let mut states = Vec::new();
+        let mut rest = xml.as_str();
+
+        while let Some(start) = rest.find("<w:ffData>") {
+            let block_end = rest[start..]
+                .find("</w:ffData>")
+                .map(|i| start + i)
+                .unwrap_or(rest.len());
+            let block = &rest[start..block_end];
+
+            if block.contains("<w:checkBox") {
+                states.push(Self::checkbox_block_state(block));
+            }
+
+            rest = &rest[block_end..];
+        }
+
+        Ok(states)
+    }
+
+    /// 解析单个 w:ffData 块的复选框状态，w:checked 优先于 w:default
+    fn checkbox_block_state(block: &str) -> bool {
+        fn flag(block: &str, tag: &str) -> Option<bool> {
+            let pos = block.find(tag)?;
+            let tail = &block[pos + tag.len()..];
+            let end = tail.find('>').unwrap_or(tail.len());
+            let attrs = &tail[..end];
+            if attrs.contains("w:val=\"0\"") || attrs.contains("w:val=\"false\"") {
+                Some(false)
+            } else {
+                Some(true)
+            }
+        }
+
+        flag(block, "<w:checked")
+            .or_else(|| flag(block, "<w:default"))
+            .unwrap_or(false)
+    }
+
+    /// 将含复选框字形的单元格文本归一化为表单值
+    ///
+    /// * `☑男 ☐女` → `男`（多个选中项以 `、` 连接）
+    /// * `性别：☐男 ☑女` → `性别：女`
+    /// * 标签在复选框之前：`同意☑` → `同意`，`男☐ 女☑` → `女`
+    /// * 单独的复选框 `☑` → `true`，`☐` → `false`；`备注：☑` → `备注：true`
+    /// * 不含复选框的文本原样返回
+    pub fn normalize_form_value(text: &str) -> String {
+        let is_box = |c: char| CHECKED_GLYPHS.contains(&c) || UNCHECKED_GLYPHS.contains(&c);
+
+        let Some(first) = text.find(is_box) else {
+            return text.to_string();
+        };
+
+        let prefix = text[..first].trim();
+        let mut options: Vec<(bool, String)> = Vec::new();
+
+        for c in text[first..].chars() {
+            if is_box(c) {
+                options.push((CHECKED_GLYPHS.contains(&c), String::new()));
+            } else if let Some((_, label)) = options.last_mut() {
+                label.push(c);
+            }
+        }
+
+        // 没有标签或前缀是字段名时视为布尔复选框
+        let field_name = prefix.is_empty() || prefix.ends_with(['：', ':']);
+        if field_name && options.iter().all(|(_, label)| label.trim().is_empty()) {
+            let checked = options.iter().any(|(checked, _)| *checked);
+            return format!("{}{}", prefix, checked);
+        }
+
+        // 以复选框结尾时标签在复选框之前：每个复选框的标签为它与上一个复选框之间的文本
+        if !field_name && options.last().is_some_and(|(_, label)| label.trim().is_empty()) {
+            let selected: Vec<&str> = text
+                .split(is_box)
+                .zip(&options)
+                .filter(|(_, (checked, _))| *checked)
+                .map(|(label, _)| label.trim())
+                .filter(|label| !label.is_empty())
+                .collect();
+            return selected.join("、");
+        }
+
+        let selected: Vec<&str> = options
+            .iter()
+            .filter(|(checked, _)| *checked)
+            .map(|(_, label)| label.trim())
+            .filter(|label| !label.is_empty())
+            .collect();
+
+        format!("{}{}", prefix, selected.join("、"))
+    }
+
+    /// 提取文档中所有文本（不分表格）
+    pub fn extract_all_text(file_path: &str) -> Result<String, String> {
+        let bytes = std::fs::read(file_path).map_err(|e| e.to_string())?;
+
+        let doc = docx_rs::read_docx(&bytes).map_err(|e| e.to_string())?;
+
+        let mut state = FormState {
+            legacy_checkboxes: Self::read_legacy_checkbox_states(&bytes)?.into_iter(),
+        };
+
+        // 表格同样需要遍历，其中的旧式复选框才能按顺序消费
+        let mut tables = Vec::new();
+        let mut all_text = String::new();
+        Self::walk_body(&doc.document.children, &mut state, &mut tables, &mut all_text)?;
+
         Ok(all_text)
     }
 }
@@ -12,6 +12,10 @@ interface FileRecord {
   file_name: string
   file_path: string
   file_type: string
+  parent_id: number | null
+  member_path: string | null
+  content_hash: string | null
+  original_path: string | null
   created_at: string
 }
 